@@ -0,0 +1,187 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{
+    ai::Engine,
+    game::{GameState, Move, Player},
+};
+
+/// Playouts stop after this many plies, long enough for any real game.
+const MAX_PLAYOUT: usize = 500;
+
+/// Monte Carlo tree search with UCT selection and uniformly random playouts.
+pub struct Mcts {
+    playouts: u32,
+    exploration: f64,
+    /// Seeds the playouts of every search, which are seeded from the clock
+    /// when unset.
+    seed: Option<u64>,
+}
+
+impl Mcts {
+    pub fn new(playouts: u32, exploration: f64) -> Self {
+        Self {
+            playouts,
+            exploration,
+            seed: None,
+        }
+    }
+
+    /// Plays the same pseudo-random playouts on every search, so the same
+    /// position always gets the same move.
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self {
+            seed: Some(seed),
+            ..self
+        }
+    }
+}
+
+struct Node {
+    mv: Option<Move>,
+    /// The player who made `mv`, whose wins are counted in `wins`.
+    player: Player,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<Move>,
+    visits: u32,
+    wins: f64,
+}
+
+impl Engine for Mcts {
+    fn choose(&self, game: &GameState) -> Option<Move> {
+        let moves = game.legal_moves();
+        if moves.len() <= 1 {
+            return moves.first().copied();
+        }
+
+        let mut rng = self.seed.map_or_else(Rng::new, Rng::seeded);
+        let mut nodes = vec![Node {
+            mv: None,
+            player: game.to_move().opponent(),
+            parent: None,
+            children: Vec::new(),
+            untried: moves,
+            visits: 0,
+            wins: 0.0,
+        }];
+
+        for _ in 0..self.playouts {
+            let mut node = 0;
+            let mut position = game.clone();
+
+            while nodes[node].untried.is_empty() && !nodes[node].children.is_empty() {
+                node = self.select(&nodes, node);
+                position.apply(nodes[node].mv.unwrap());
+            }
+
+            if !nodes[node].untried.is_empty() {
+                let index = rng.below(nodes[node].untried.len());
+                let mv = nodes[node].untried.swap_remove(index);
+                let player = position.to_move();
+                position.apply(mv);
+
+                nodes.push(Node {
+                    mv: Some(mv),
+                    player,
+                    parent: Some(node),
+                    children: Vec::new(),
+                    untried: position.legal_moves(),
+                    visits: 0,
+                    wins: 0.0,
+                });
+                let child = nodes.len() - 1;
+                nodes[node].children.push(child);
+                node = child;
+            }
+
+            let winner = playout(position, &mut rng);
+
+            let mut current = Some(node);
+            while let Some(index) = current {
+                let node = &mut nodes[index];
+                node.visits += 1;
+                node.wins += match winner {
+                    Some(winner) if winner == node.player => 1.0,
+                    Some(_) => 0.0,
+                    None => 0.5,
+                };
+                current = node.parent;
+            }
+        }
+
+        nodes[0]
+            .children
+            .iter()
+            .max_by_key(|&&child| nodes[child].visits)
+            .and_then(|&child| nodes[child].mv)
+    }
+}
+
+impl Mcts {
+    fn select(&self, nodes: &[Node], parent: usize) -> usize {
+        let log_visits = (nodes[parent].visits as f64).ln();
+        let uct = |child: usize| {
+            let node = &nodes[child];
+            let visits = node.visits as f64;
+            node.wins / visits + self.exploration * (log_visits / visits).sqrt()
+        };
+
+        *nodes[parent]
+            .children
+            .iter()
+            .max_by(|&&a, &&b| uct(a).total_cmp(&uct(b)))
+            .unwrap()
+    }
+}
+
+fn playout(mut position: GameState, rng: &mut Rng) -> Option<Player> {
+    for _ in 0..MAX_PLAYOUT {
+        let moves = position.legal_moves();
+        if moves.is_empty() {
+            break;
+        }
+        position.apply(moves[rng.below(moves.len())]);
+    }
+
+    position.winner()
+}
+
+/// A xorshift generator, plenty for picking playout moves.
+struct Rng(u64);
+
+impl Rng {
+    fn new() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64);
+        Self::seeded(seed)
+    }
+
+    fn seeded(seed: u64) -> Self {
+        Self(seed | 1)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::SQRT_2;
+
+    use super::*;
+    use crate::board::Board;
+
+    #[test]
+    fn chooses_a_legal_move() {
+        let game = GameState::new(Board::new(), Player::White);
+        let engine = Mcts::new(500, SQRT_2).with_seed(0x9e37_79b9_7f4a_7c15);
+
+        let mv = engine.choose(&game).unwrap();
+        assert!(game.legal_moves().contains(&mv));
+    }
+}
@@ -0,0 +1,289 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    ai::{Engine, Strength},
+    bitboard::Bitboard,
+    colors::Colors,
+    game::{GameState, Move, MoveKind, Player},
+    zobrist::{Bound, Entry, TranspositionTable},
+};
+
+const WIN: i32 = 1_000_000;
+
+/// Scores this close to `WIN` are wins found by the search, counted in plies
+/// from the root.
+const WON: i32 = WIN - 1_000;
+
+/// Alpha-beta search, deepened one ply at a time until the depth limit is
+/// reached or the time budget runs out. Results are kept in a transposition
+/// table between iterations and between moves.
+pub struct Minimax {
+    depth: u32,
+    budget: Duration,
+    table: Mutex<TranspositionTable>,
+}
+
+impl Minimax {
+    pub fn new(strength: Strength, table_megabytes: usize) -> Self {
+        Self {
+            depth: strength.depth(),
+            budget: strength.budget(),
+            table: Mutex::new(TranspositionTable::new(table_megabytes)),
+        }
+    }
+}
+
+impl Engine for Minimax {
+    fn choose(&self, game: &GameState) -> Option<Move> {
+        let mut moves = game.legal_moves();
+        if moves.is_empty() {
+            return None;
+        }
+
+        let mut position = Bitboard::from_game(game);
+        let mut table = self.table.lock().unwrap();
+        let mut search = Search {
+            deadline: Instant::now() + self.budget,
+            nodes: 0,
+            table: &mut table,
+            passes: game
+                .passes()
+                .iter()
+                .map(|&(player, color)| position.hash_with(player, Some(color)))
+                .collect(),
+            chain: 0,
+            last_mover: game.last_mover(),
+        };
+        let mut best = moves[0];
+
+        for depth in 1..=self.depth {
+            let mut alpha = -WIN - 1;
+            let mut depth_best = None;
+
+            for &mv in moves.iter() {
+                let score = match search.score(&mut position, mv, depth - 1, 1, alpha, WIN + 1) {
+                    Some(score) => score,
+                    None => return Some(best),
+                };
+
+                if depth_best.is_none() || score > alpha {
+                    alpha = score;
+                    depth_best = Some(mv);
+                }
+            }
+
+            best = depth_best.unwrap();
+
+            // Search the best move first on the next iteration, it gives the
+            // tightest bounds.
+            let index = moves.iter().position(|&mv| mv == best).unwrap();
+            moves[..=index].rotate_right(1);
+
+            if alpha.abs() >= WIN - self.depth as i32 {
+                break;
+            }
+        }
+
+        Some(best)
+    }
+}
+
+struct Search<'a> {
+    deadline: Instant,
+    nodes: u64,
+    table: &'a mut TranspositionTable,
+    /// Hashes of the positions passed from along the current line.
+    passes: Vec<u64>,
+    /// Where the passes since the last real move start in `passes`.
+    chain: usize,
+    last_mover: Option<Player>,
+}
+
+impl Search<'_> {
+    /// The score of playing `mv` in `position`, from the point of view of the
+    /// side making it. A sumo push keeps the turn, so the sign only flips when
+    /// the side to move changes.
+    ///
+    /// A pass that brings the position back to one already passed from since
+    /// the last real move is a deadlock, lost by whoever made that move.
+    fn score(
+        &mut self,
+        position: &mut Bitboard,
+        mv: Move,
+        depth: u32,
+        ply: i32,
+        alpha: i32,
+        beta: i32,
+    ) -> Option<i32> {
+        let player = position.to_move();
+        let chain = self.chain;
+        let last_mover = self.last_mover;
+
+        if mv.kind == MoveKind::Pass {
+            self.passes.push(position.hash());
+        } else {
+            self.chain = self.passes.len();
+            self.last_mover = Some(player);
+        }
+
+        let undo = position.make(mv);
+
+        let score =
+            if mv.kind == MoveKind::Pass && self.passes[self.chain..].contains(&position.hash()) {
+                Some(self.last_mover.map_or(0, |loser| {
+                    if loser == player {
+                        ply - WIN
+                    } else {
+                        WIN - ply
+                    }
+                }))
+            } else if position.to_move() == player {
+                self.negamax(position, depth, ply, alpha, beta)
+            } else {
+                self.negamax(position, depth, ply, -beta, -alpha)
+                    .map(|score| -score)
+            };
+
+        position.unmake(undo);
+
+        if mv.kind == MoveKind::Pass {
+            self.passes.pop();
+        }
+        self.chain = chain;
+        self.last_mover = last_mover;
+
+        score
+    }
+
+    /// `None` when the time budget ran out before the search finished.
+    fn negamax(
+        &mut self,
+        position: &mut Bitboard,
+        depth: u32,
+        ply: i32,
+        mut alpha: i32,
+        beta: i32,
+    ) -> Option<i32> {
+        self.nodes += 1;
+        if self.nodes & 1023 == 0 && Instant::now() >= self.deadline {
+            return None;
+        }
+
+        if let Some(winner) = position.winner() {
+            return Some(if winner == position.to_move() {
+                WIN - ply
+            } else {
+                ply - WIN
+            });
+        }
+
+        if depth == 0 {
+            return Some(evaluate(position, position.to_move()));
+        }
+
+        // Scores found in the middle of a run of passes depend on the passes
+        // before them, so they are neither trusted nor stored.
+        let key = position.hash();
+        let in_chain = self.passes.len() > self.chain;
+        let entry = self.table.probe(key);
+
+        if let Some(entry) = entry.filter(|entry| entry.depth >= depth && !in_chain) {
+            let score = from_table(entry.score, ply);
+            match entry.bound {
+                Bound::Exact => return Some(score),
+                Bound::Lower if score >= beta => return Some(score),
+                Bound::Upper if score <= alpha => return Some(score),
+                _ => {}
+            }
+        }
+
+        let mut moves = Vec::new();
+        position.legal_moves(&mut moves);
+
+        if let Some(index) = entry
+            .and_then(|entry| entry.best)
+            .and_then(|best| moves.iter().position(|&mv| mv == best))
+        {
+            moves[..=index].rotate_right(1);
+        }
+
+        let original_alpha = alpha;
+        let mut best = -WIN - 1;
+        let mut best_move = None;
+
+        for mv in moves {
+            let score = self.score(position, mv, depth - 1, ply + 1, alpha, beta)?;
+
+            if score > best {
+                best = score;
+                best_move = Some(mv);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        if !in_chain {
+            self.table.store(Entry {
+                key,
+                depth,
+                score: to_table(best, ply),
+                bound: if best <= original_alpha {
+                    Bound::Upper
+                } else if best >= beta {
+                    Bound::Lower
+                } else {
+                    Bound::Exact
+                },
+                best: best_move,
+            });
+        }
+
+        Some(best)
+    }
+}
+
+/// Win scores are stored counted from the node rather than from the root, so
+/// they stay right when the position is reached at another ply.
+fn to_table(score: i32, ply: i32) -> i32 {
+    if score >= WON {
+        score + ply
+    } else if score <= -WON {
+        score - ply
+    } else {
+        score
+    }
+}
+
+fn from_table(score: i32, ply: i32) -> i32 {
+    if score >= WON {
+        score - ply
+    } else if score <= -WON {
+        score + ply
+    } else {
+        score
+    }
+}
+
+/// Rewards towers for how far they have advanced and, far more, for having an
+/// open line to the opponent's home row, since forcing such a tower wins.
+fn evaluate(position: &Bitboard, player: Player) -> i32 {
+    let mut score = 0;
+
+    for (side, sign) in [(player, 1), (player.opponent(), -1)] {
+        for color in Colors::ALL {
+            let (_, y) = position.tower(side, color);
+            score += sign * y.abs_diff(side.home_row()) as i32;
+
+            if position.reaches_goal(side, color) {
+                score += sign * 10;
+            }
+        }
+    }
+
+    score
+}
@@ -0,0 +1,107 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    config,
+    game::{GameState, Move},
+};
+
+use self::{mcts::Mcts, minimax::Minimax};
+
+pub mod mcts;
+pub mod minimax;
+
+/// A computer player. Engines only see the rules engine, so the same player can
+/// sit behind the board, a benchmark or a server.
+pub trait Engine: Send + Sync {
+    /// The move to play in `game`, `None` once the game is over.
+    fn choose(&self, game: &GameState) -> Option<Move>;
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EngineKind {
+    Minimax,
+    Mcts,
+}
+
+impl EngineKind {
+    pub const ALL: [EngineKind; 2] = [EngineKind::Minimax, EngineKind::Mcts];
+
+    pub fn build(&self, strength: Strength) -> Arc<dyn Engine> {
+        match *self {
+            EngineKind::Minimax => Arc::new(Minimax::new(strength, config::TRANSPOSITION_TABLE_MB)),
+            EngineKind::Mcts => {
+                let mcts = Mcts::new(strength.playouts(), config::MCTS_EXPLORATION);
+                match config::MCTS_SEED {
+                    Some(seed) => Arc::new(mcts.with_seed(seed)),
+                    None => Arc::new(mcts),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Strength {
+    Beginner,
+    Intermediate,
+    Expert,
+}
+
+impl Strength {
+    /// The deepest search in plies.
+    pub fn depth(&self) -> u32 {
+        match *self {
+            Strength::Beginner => 2,
+            Strength::Intermediate => 6,
+            Strength::Expert => 64,
+        }
+    }
+
+    /// How long a search may take before the best move so far is played.
+    pub fn budget(&self) -> Duration {
+        match *self {
+            Strength::Beginner => Duration::from_millis(200),
+            Strength::Intermediate => Duration::from_secs(1),
+            Strength::Expert => Duration::from_secs(3),
+        }
+    }
+
+    /// Random playouts per move for tree search.
+    pub fn playouts(&self) -> u32 {
+        match *self {
+            Strength::Beginner => 500,
+            Strength::Intermediate => 5_000,
+            Strength::Expert => 20_000,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{board::Board, position::read_position};
+
+    /// White's forced orange tower reaches Black's home row only on a8.
+    const WIN_IN_ONE: &str = "1grykpbo/8/2O5/8/8/n7/8/1BPKYRGN w o";
+
+    #[test]
+    fn every_engine_finds_a_win_in_one() {
+        for kind in EngineKind::ALL {
+            let engine: Box<dyn Engine> = match kind {
+                EngineKind::Minimax => Box::new(Minimax::new(Strength::Beginner, 1)),
+                EngineKind::Mcts => Box::new(
+                    Mcts::new(2_000, config::MCTS_EXPLORATION).with_seed(0x2545_f491_4f6c_dd1d),
+                ),
+            };
+            let mut game = read_position(WIN_IN_ONE, Board::new()).unwrap();
+
+            let mv = engine.choose(&game).unwrap();
+            assert!(game.legal_moves().contains(&mv), "{:?}", kind);
+            assert_eq!(mv.to, (0, 7), "{:?}", kind);
+
+            game.apply(mv);
+            assert!(game.is_terminal());
+            assert_eq!(engine.choose(&game), None, "{:?}", kind);
+        }
+    }
+}
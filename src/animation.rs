@@ -0,0 +1,95 @@
+use std::f32::consts::PI;
+
+use bevy::{
+    math::Vec3,
+    prelude::{Component, Query, Res, ResMut, Time, Transform},
+};
+
+use crate::{config, menu::Settings, pieces::Piece};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Easing {
+    Linear,
+    QuadInOut,
+    CubicInOut,
+    SineInOut,
+}
+
+impl Easing {
+    pub const ALL: [Easing; 4] = [
+        Easing::Linear,
+        Easing::QuadInOut,
+        Easing::CubicInOut,
+        Easing::SineInOut,
+    ];
+
+    /// How far along the path a piece is after `t` of the animation, both
+    /// running from 0 to 1.
+    pub fn ease(&self, t: f32) -> f32 {
+        match *self {
+            Easing::Linear => t,
+            Easing::QuadInOut if t < 0.5 => 2.0 * t * t,
+            Easing::QuadInOut => 1.0 - (2.0 - 2.0 * t).powi(2) / 2.0,
+            Easing::CubicInOut if t < 0.5 => 4.0 * t * t * t,
+            Easing::CubicInOut => 1.0 - (2.0 - 2.0 * t).powi(3) / 2.0,
+            Easing::SineInOut => (1.0 - (PI * t).cos()) / 2.0,
+        }
+    }
+}
+
+/// A piece sliding in a straight line from where it was when its square
+/// changed, which follows the file or diagonal it moved along.
+#[derive(Component, Default)]
+pub struct Tween {
+    from: Vec3,
+    to: Option<Vec3>,
+    elapsed: f32,
+}
+
+/// Whether any piece is still sliding. Input waits for it, so every move can
+/// be followed before the next one starts.
+#[derive(Default)]
+pub struct Animating(pub bool);
+
+/// Starts a tween for every piece whose square changed and advances them all.
+/// Pieces jump straight to their first square when spawned.
+pub fn move_pieces(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut animating: ResMut<Animating>,
+    mut pieces: Query<(&Piece, &mut Tween, &mut Transform)>,
+) {
+    let start = -config::BOARD_SIZE / 2.0 + config::TILE_SIZE / 2.0;
+    let mut in_flight = false;
+
+    for (piece, mut tween, mut transform) in pieces.iter_mut() {
+        let target = Vec3::new(
+            start + config::TILE_SIZE * piece.x() as f32,
+            start + config::TILE_SIZE * piece.y() as f32,
+            transform.translation.z,
+        );
+
+        match tween.to {
+            Some(to) if to == target => {}
+            Some(_) => {
+                tween.from = transform.translation;
+                tween.to = Some(target);
+                tween.elapsed = 0.0;
+            }
+            None => {
+                transform.translation = target;
+                tween.to = Some(target);
+                tween.elapsed = config::ANIMATION_SECONDS;
+            }
+        }
+
+        if tween.elapsed < config::ANIMATION_SECONDS {
+            tween.elapsed += time.delta_seconds();
+            let t = (tween.elapsed / config::ANIMATION_SECONDS).min(1.0);
+            transform.translation = tween.from.lerp(target, settings.easing.ease(t));
+            in_flight |= t < 1.0;
+        }
+    }
+
+    animating.0 = in_flight;
+}
@@ -0,0 +1,378 @@
+use crate::{
+    colors::Colors,
+    game::{GameState, Move, MoveKind, Player, Rank},
+    zobrist::KEYS,
+};
+
+/// A compact copy of a position for searching: one occupancy bit per square,
+/// numbered `y * 8 + x`, and the square of every tower by player and colour.
+///
+/// Moves are made and unmade in place, keeping a Zobrist hash of the position
+/// up to date. Unlike `GameState` it keeps no pass history, so it does not
+/// detect deadlocks by itself; comparing hashes along a line does.
+#[derive(Clone)]
+pub struct Bitboard {
+    occupied: u64,
+    towers: [[u8; 8]; 2],
+    ranks: [[Rank; 8]; 2],
+    tiles: [u8; 64],
+    to_move: Player,
+    forced: Option<Colors>,
+    hash: u64,
+}
+
+/// What `Bitboard::make` changed, for `Bitboard::unmake` to put back.
+#[derive(Clone, Copy)]
+pub struct Undo {
+    from: u8,
+    to: u8,
+    color: usize,
+    to_move: Player,
+    forced: Option<Colors>,
+    pushed: [usize; 3],
+    pushed_len: usize,
+    hash: u64,
+}
+
+impl Bitboard {
+    pub fn from_game(game: &GameState) -> Self {
+        let mut bitboard = Self {
+            occupied: 0,
+            towers: [[0; 8]; 2],
+            ranks: [[Rank::Normal; 8]; 2],
+            tiles: [0; 64],
+            to_move: game.to_move(),
+            forced: game.forced(),
+            hash: KEYS.to_move(game.to_move()) ^ KEYS.forced(game.forced()),
+        };
+
+        for x in 0..8 {
+            for y in 0..8 {
+                bitboard.tiles[square((x, y)) as usize] = game.board().tiles()[x][y].index() as u8;
+            }
+        }
+
+        for player in [Player::White, Player::Black] {
+            for color in Colors::ALL {
+                let at = square(game.tower(player, color));
+                bitboard.occupied |= 1 << at;
+                bitboard.hash ^= KEYS.tower(player, color.index(), at);
+                bitboard.towers[player as usize][color.index()] = at;
+                bitboard.ranks[player as usize][color.index()] = game.rank(player, color);
+                bitboard.hash ^= KEYS.rank(player, color.index(), game.rank(player, color));
+            }
+        }
+
+        bitboard
+    }
+
+    pub fn to_move(&self) -> Player {
+        self.to_move
+    }
+
+    /// The Zobrist hash of the tower squares, side to move and forced colour.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// The hash of the same towers with another side to move and forced colour.
+    pub fn hash_with(&self, to_move: Player, forced: Option<Colors>) -> u64 {
+        self.hash
+            ^ KEYS.to_move(self.to_move)
+            ^ KEYS.forced(self.forced)
+            ^ KEYS.to_move(to_move)
+            ^ KEYS.forced(forced)
+    }
+
+    pub fn tower(&self, player: Player, color: Colors) -> (usize, usize) {
+        coords(self.towers[player as usize][color.index()])
+    }
+
+    /// The player with a tower on the opponent's home row, if any.
+    pub fn winner(&self) -> Option<Player> {
+        const ROW: u64 = 0xff;
+
+        [Player::White, Player::Black].into_iter().find(|&player| {
+            let goal = ROW << (8 * player.opponent().home_row());
+            self.towers[player as usize]
+                .iter()
+                .any(|&at| goal & (1 << at) != 0)
+        })
+    }
+
+    /// Clears `moves` and fills it with the same moves, in the same order, as
+    /// `GameState::legal_moves`.
+    pub fn legal_moves(&self, moves: &mut Vec<Move>) {
+        moves.clear();
+
+        if self.winner().is_some() {
+            return;
+        }
+
+        match self.forced {
+            None => {
+                for color in 0..8 {
+                    self.push_normal_moves(color, moves);
+                }
+            }
+            Some(color) => {
+                let color = color.index();
+                self.push_normal_moves(color, moves);
+
+                let from = coords(self.towers[self.to_move as usize][color]);
+                if let Some(to) = self.sumo_push(color) {
+                    moves.push(Move {
+                        from,
+                        to: coords(to),
+                        kind: MoveKind::SumoPush,
+                    });
+                }
+
+                if moves.is_empty() {
+                    moves.push(Move {
+                        from,
+                        to: from,
+                        kind: MoveKind::Pass,
+                    });
+                }
+            }
+        }
+    }
+
+    fn push_normal_moves(&self, color: usize, moves: &mut Vec<Move>) {
+        let player = self.to_move;
+        let at = self.towers[player as usize][color];
+        let max = self.ranks[player as usize][color].max_distance();
+
+        for dx in [0, -1, 1] {
+            let mut current = at;
+            for _ in 0..max {
+                current = match step(current, player, dx) {
+                    Some(next) if self.occupied & (1 << next) == 0 => next,
+                    _ => break,
+                };
+                moves.push(Move {
+                    from: coords(at),
+                    to: coords(current),
+                    kind: MoveKind::Normal,
+                });
+            }
+        }
+    }
+
+    /// Whether the tower has an open line to the opponent's home row.
+    pub fn reaches_goal(&self, player: Player, color: Colors) -> bool {
+        let at = self.towers[player as usize][color.index()];
+        let max = self.ranks[player as usize][color.index()].max_distance();
+        let goal = player.opponent().home_row();
+
+        [0, -1, 1].into_iter().any(|dx| {
+            let mut current = at;
+            for _ in 0..max {
+                current = match step(current, player, dx) {
+                    Some(next) if self.occupied & (1 << next) == 0 => next,
+                    _ => return false,
+                };
+                if coords(current).1 == goal {
+                    return true;
+                }
+            }
+            false
+        })
+    }
+
+    fn tower_at(&self, player: Player, at: u8) -> Option<usize> {
+        if self.occupied & (1 << at) == 0 {
+            return None;
+        }
+        self.towers[player as usize]
+            .iter()
+            .position(|&tower| tower == at)
+    }
+
+    fn sumo_push(&self, color: usize) -> Option<u8> {
+        let player = self.to_move;
+        let opponent = player.opponent();
+        let rank = self.ranks[player as usize][color];
+        let front = step(self.towers[player as usize][color], player, 0)?;
+
+        let mut current = front;
+        let mut pushed = 0;
+        loop {
+            if self.occupied & (1 << current) == 0 {
+                return Some(front).filter(|_| pushed > 0);
+            }
+
+            match self.tower_at(opponent, current) {
+                Some(other)
+                    if self.ranks[opponent as usize][other] < rank
+                        && pushed < rank.push_strength() =>
+                {
+                    pushed += 1
+                }
+                _ => return None,
+            }
+
+            current = step(current, player, 0)?;
+        }
+    }
+
+    pub fn make(&mut self, mv: Move) -> Undo {
+        let player = self.to_move;
+        let opponent = player.opponent();
+        let from = square(mv.from);
+        let to = square(mv.to);
+        let color = self.tower_at(player, from).unwrap();
+
+        let mut undo = Undo {
+            from,
+            to,
+            color,
+            to_move: player,
+            forced: self.forced,
+            pushed: [0; 3],
+            pushed_len: 0,
+            hash: self.hash,
+        };
+
+        let mut forced_square = to;
+
+        if mv.kind == MoveKind::SumoPush {
+            let mut current = Some(to);
+            while let Some(other) = current.and_then(|at| self.tower_at(opponent, at)) {
+                undo.pushed[undo.pushed_len] = other;
+                undo.pushed_len += 1;
+                current = step(self.towers[opponent as usize][other], player, 0);
+            }
+
+            for &other in undo.pushed[..undo.pushed_len].iter() {
+                self.occupied &= !(1 << self.towers[opponent as usize][other]);
+            }
+            for &other in undo.pushed[..undo.pushed_len].iter() {
+                let tower = &mut self.towers[opponent as usize][other];
+                self.hash ^= KEYS.tower(opponent, other, *tower);
+                *tower = step(*tower, player, 0).unwrap();
+                self.hash ^= KEYS.tower(opponent, other, *tower);
+                self.occupied |= 1 << *tower;
+                forced_square = *tower;
+            }
+        }
+
+        self.occupied &= !(1 << from);
+        self.occupied |= 1 << to;
+        self.towers[player as usize][color] = to;
+        self.hash ^= KEYS.tower(player, color, from) ^ KEYS.tower(player, color, to);
+
+        self.hash ^= KEYS.forced(self.forced);
+        self.forced = Some(Colors::ALL[self.tiles[forced_square as usize] as usize]);
+        self.hash ^= KEYS.forced(self.forced);
+
+        if mv.kind != MoveKind::SumoPush {
+            self.to_move = opponent;
+            self.hash ^= KEYS.to_move(player) ^ KEYS.to_move(opponent);
+        }
+
+        undo
+    }
+
+    pub fn unmake(&mut self, undo: Undo) {
+        let player = undo.to_move;
+        let opponent = player.opponent();
+
+        self.occupied &= !(1 << undo.to);
+        self.occupied |= 1 << undo.from;
+        self.towers[player as usize][undo.color] = undo.from;
+
+        let pushed = &undo.pushed[..undo.pushed_len];
+        for &other in pushed.iter() {
+            self.occupied &= !(1 << self.towers[opponent as usize][other]);
+        }
+        for &other in pushed.iter() {
+            let tower = &mut self.towers[opponent as usize][other];
+            *tower = step(*tower, opponent, 0).unwrap();
+            self.occupied |= 1 << *tower;
+        }
+
+        self.to_move = player;
+        self.forced = undo.forced;
+        self.hash = undo.hash;
+    }
+}
+
+fn square((x, y): (usize, usize)) -> u8 {
+    (y * 8 + x) as u8
+}
+
+fn coords(at: u8) -> (usize, usize) {
+    (at as usize % 8, at as usize / 8)
+}
+
+/// One square towards the opponent's home row, shifted a column by `dx`.
+fn step(at: u8, player: Player, dx: isize) -> Option<u8> {
+    let (x, y) = coords(at);
+    let x = x.checked_add_signed(dx).filter(|&x| x < 8)?;
+    let y = match player {
+        Player::White => Some(y + 1).filter(|&y| y < 8)?,
+        Player::Black => y.checked_sub(1)?,
+    };
+    Some(square((x, y)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{board::Board, test_support::Rng};
+
+    /// Plays pseudo-random games on both representations side by side.
+    #[test]
+    fn agrees_with_game_state() {
+        let mut rng = Rng::new(0x2545_f491_4f6c_dd1d);
+        let mut moves = Vec::new();
+
+        for game_number in 0..400 {
+            let first = [Player::White, Player::Black][game_number % 2];
+            let mut game = GameState::new(Board::new(), first);
+
+            // Every other game gives the towers random sumo ranks.
+            if game_number % 4 >= 2 {
+                let mut ranks = [[Rank::Normal; 8]; 2];
+                for rank in ranks.iter_mut().flatten() {
+                    *rank = Rank::ALL[rng.below(4)];
+                }
+                game = GameState::ranked_opening(ranks, first);
+            }
+
+            let mut bitboard = Bitboard::from_game(&game);
+
+            while !game.is_terminal() {
+                bitboard.legal_moves(&mut moves);
+                assert_eq!(moves, game.legal_moves());
+
+                let mv = moves[rng.below(moves.len())];
+
+                let before = bitboard.clone();
+                let undo = bitboard.make(mv);
+                bitboard.unmake(undo);
+                assert_eq!(bitboard.occupied, before.occupied);
+                assert_eq!(bitboard.towers, before.towers);
+                assert_eq!(bitboard.forced, before.forced);
+                assert_eq!(bitboard.hash, before.hash);
+
+                bitboard.make(mv);
+                game.apply(mv);
+                assert_eq!(bitboard.to_move(), game.to_move());
+                assert_eq!(bitboard.forced, game.forced());
+                assert_eq!(bitboard.hash, Bitboard::from_game(&game).hash);
+                for player in [Player::White, Player::Black] {
+                    for color in Colors::ALL {
+                        assert_eq!(bitboard.tower(player, color), game.tower(player, color));
+                    }
+                }
+            }
+
+            if game.result().map(|(_, reason)| reason) == Some(crate::game::WinReason::HomeRow) {
+                assert_eq!(bitboard.winner(), game.winner());
+            }
+        }
+    }
+}
@@ -1,12 +1,26 @@
 use bevy::{
     math::Vec3,
-    prelude::{Commands, Transform},
+    prelude::{Commands, Component, Query, Res, Transform},
     sprite::{Sprite, SpriteBundle},
 };
 
-use crate::{colors::Colors, config};
+use crate::{colors::Colors, config, game::GameState, InGame};
 
+/// How the colours are laid out. The mirrored board swaps the left and right
+/// files, which keeps every row and file to one tile of each colour.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Layout {
+    Standard,
+    Mirrored,
+}
+
+impl Layout {
+    pub const ALL: [Layout; 2] = [Layout::Standard, Layout::Mirrored];
+}
+
+#[derive(Clone)]
 pub struct Board {
+    layout: Layout,
     tiles: [[Colors; 8]; 8],
 }
 
@@ -14,11 +28,16 @@ impl Board {
     pub fn tiles(&self) -> &[[Colors; 8]; 8] {
         &self.tiles
     }
+
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
 }
 
 impl Board {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self {
+            layout: Layout::Standard,
             tiles: [
                 [
                     Colors::Orange,
@@ -103,10 +122,25 @@ impl Board {
             ],
         }
     }
+
+    pub fn with_layout(layout: Layout) -> Self {
+        let mut board = Board::new();
+        if layout == Layout::Mirrored {
+            board.tiles.reverse();
+        }
+        board.layout = layout;
+        board
+    }
 }
 
-pub fn create_board(mut commands: Commands) {
-    let board = Board::new();
+#[derive(Component)]
+pub struct Tile {
+    x: usize,
+    y: usize,
+}
+
+pub fn create_board(mut commands: Commands, game: Res<GameState>) {
+    let board = game.board();
 
     let tile_size = config::BOARD_SIZE / 8.0;
     let scale = Vec3::new(tile_size, tile_size, 0.0);
@@ -120,20 +154,36 @@ pub fn create_board(mut commands: Commands) {
                 0.0,
             );
 
-            commands.spawn_bundle(SpriteBundle {
-                sprite: Sprite {
-                    color: board.tiles()[x][y].color(),
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: board.tiles()[x][y].color(),
+                        ..Default::default()
+                    },
+                    transform: Transform {
+                        translation,
+                        scale,
+                        ..Default::default()
+                    },
                     ..Default::default()
-                },
-                transform: Transform {
-                    translation,
-                    scale,
-                    ..Default::default()
-                },
-                ..Default::default()
-            });
+                })
+                .insert(Tile { x, y })
+                .insert(InGame);
         }
     }
+}
+
+/// Recolours the tiles for the board of the game on show, which changes when
+/// a record played on another layout is loaded or replayed.
+pub fn paint_board(game: Res<GameState>, mut tiles: Query<(&Tile, &mut Sprite)>) {
+    if !game.is_changed() {
+        return;
+    }
 
-    commands.insert_resource(board);
+    for (tile, mut sprite) in tiles.iter_mut() {
+        let color = game.board().tiles()[tile.x][tile.y].color();
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
 }
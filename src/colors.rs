@@ -1,6 +1,6 @@
 use bevy::prelude::Color;
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Colors {
     Orange,
     Blue,
@@ -13,6 +13,42 @@ pub enum Colors {
 }
 
 impl Colors {
+    pub const ALL: [Colors; 8] = [
+        Colors::Orange,
+        Colors::Blue,
+        Colors::Purple,
+        Colors::Pink,
+        Colors::Yellow,
+        Colors::Red,
+        Colors::Green,
+        Colors::Brown,
+    ];
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// The letter naming this colour in position strings, Pink and Brown taking
+    /// `k` and `n` since Purple and Blue have `p` and `b`.
+    pub fn letter(&self) -> char {
+        match *self {
+            Colors::Orange => 'o',
+            Colors::Blue => 'b',
+            Colors::Purple => 'p',
+            Colors::Pink => 'k',
+            Colors::Yellow => 'y',
+            Colors::Red => 'r',
+            Colors::Green => 'g',
+            Colors::Brown => 'n',
+        }
+    }
+
+    pub fn from_letter(letter: char) -> Option<Self> {
+        Colors::ALL
+            .into_iter()
+            .find(|color| color.letter() == letter)
+    }
+
     pub fn color(&self) -> Color {
         match *self {
             Colors::Orange => Color::rgb(0.839, 0.458, 0.129),
@@ -1,2 +1,38 @@
+use std::f64::consts::SQRT_2;
+
+use crate::{
+    ai::{EngineKind, Strength},
+    animation::Easing,
+    board::Layout,
+    game::Player,
+    match_play::MatchLength,
+};
+
 pub const BOARD_SIZE: f32 = 600.0;
 pub const TILE_SIZE: f32 = BOARD_SIZE / 8.0;
+/// How long a piece takes to slide to its new square.
+pub const ANIMATION_SECONDS: f32 = 0.3;
+/// The easing the menu starts with.
+pub const ANIMATION_EASING: Easing = Easing::CubicInOut;
+pub const FONT: &str = "fonts/DejaVuSansMono.ttf";
+/// How many lines the history panel shows. Beyond that the latest moves are
+/// listed under a count of the earlier ones.
+pub const HISTORY_LINES: usize = 32;
+/// A position string to play from instead of the opening, see
+/// `position::write_position`.
+pub const START_POSITION: Option<&str> = None;
+/// The settings the menu starts with.
+pub const FIRST_PLAYER: Player = Player::White;
+pub const MATCH_LENGTH: MatchLength = MatchLength::Standard;
+pub const BOARD_LAYOUT: Layout = Layout::Standard;
+pub const COMPUTER_PLAYER: Option<Player> = None;
+pub const COMPUTER_ENGINE: EngineKind = EngineKind::Minimax;
+pub const COMPUTER_STRENGTH: Strength = Strength::Intermediate;
+pub const TRANSPOSITION_TABLE_MB: usize = 16;
+/// How far tree search favours little-visited moves over the best so far.
+pub const MCTS_EXPLORATION: f64 = SQRT_2;
+/// Seeds tree search's random playouts, so the computer repeats its games.
+/// Unset, every search is seeded from the clock.
+pub const MCTS_SEED: Option<u64> = None;
+/// Where Ctrl+S saves the match and Ctrl+O loads it from.
+pub const RECORD_FILE: &str = "kamisado.txt";
@@ -0,0 +1,582 @@
+use crate::{board::Board, colors::Colors};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Player {
+    White,
+    Black,
+}
+
+impl Player {
+    /// The row this player's towers start on.
+    pub fn home_row(&self) -> usize {
+        match *self {
+            Player::White => 0,
+            Player::Black => 7,
+        }
+    }
+
+    pub fn opponent(&self) -> Self {
+        match *self {
+            Player::White => Player::Black,
+            Player::Black => Player::White,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MoveKind {
+    Normal,
+    /// A blocked tower's zero-length move.
+    Pass,
+    /// A sumo stepping forward and pushing the towers in front of it back.
+    SumoPush,
+}
+
+/// How often a tower has scored in a match. Each rank shortens its moves and
+/// lets it push more towers.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Rank {
+    Normal,
+    Sumo,
+    DoubleSumo,
+    TripleSumo,
+    QuadrupleSumo,
+}
+
+impl Rank {
+    pub const ALL: [Rank; 5] = [
+        Rank::Normal,
+        Rank::Sumo,
+        Rank::DoubleSumo,
+        Rank::TripleSumo,
+        Rank::QuadrupleSumo,
+    ];
+
+    pub fn teeth(&self) -> usize {
+        *self as usize
+    }
+
+    pub fn max_distance(&self) -> usize {
+        match *self {
+            Rank::Normal => 7,
+            Rank::Sumo => 5,
+            Rank::DoubleSumo => 3,
+            Rank::TripleSumo | Rank::QuadrupleSumo => 1,
+        }
+    }
+
+    /// The number of lower ranked opponent towers it can push at once.
+    pub fn push_strength(&self) -> usize {
+        match *self {
+            Rank::Normal => 0,
+            Rank::Sumo => 1,
+            Rank::DoubleSumo => 2,
+            Rank::TripleSumo | Rank::QuadrupleSumo => 3,
+        }
+    }
+
+    /// Points scored when a tower of this rank wins a round.
+    pub fn points(&self) -> u32 {
+        match *self {
+            Rank::Normal => 1,
+            Rank::Sumo => 3,
+            Rank::DoubleSumo => 7,
+            Rank::TripleSumo | Rank::QuadrupleSumo => 15,
+        }
+    }
+
+    pub fn promoted(&self) -> Self {
+        match *self {
+            Rank::Normal => Rank::Sumo,
+            Rank::Sumo => Rank::DoubleSumo,
+            Rank::DoubleSumo => Rank::TripleSumo,
+            Rank::TripleSumo | Rank::QuadrupleSumo => Rank::QuadrupleSumo,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Move {
+    pub from: (usize, usize),
+    pub to: (usize, usize),
+    pub kind: MoveKind,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WinReason {
+    /// A tower reached the opponent's home row.
+    HomeRow,
+    /// The loser's move left both sides passing in a cycle.
+    Deadlock,
+}
+
+impl WinReason {
+    pub fn description(&self) -> &'static str {
+        match *self {
+            WinReason::HomeRow => "a tower reached the home row",
+            WinReason::Deadlock => "the opponent caused a deadlock",
+        }
+    }
+}
+
+/// The rules of the game, independent of any rendering or input handling.
+#[derive(Clone)]
+pub struct GameState {
+    board: Board,
+    towers: [[(usize, usize); 8]; 2],
+    ranks: [[Rank; 8]; 2],
+    to_move: Player,
+    forced: Option<Colors>,
+    last_mover: Option<Player>,
+    passes: Vec<(Player, Colors)>,
+    deadlocked: bool,
+}
+
+impl GameState {
+    /// Every tower starts on the home-row square of its own colour, White on
+    /// row 0 and Black on row 7, with `first` to open.
+    pub fn new(board: Board, first: Player) -> Self {
+        let mut towers = [[(0, 0); 8]; 2];
+
+        for x in 0..8 {
+            towers[Player::White as usize][board.tiles()[x][0].index()] = (x, 0);
+            towers[Player::Black as usize][board.tiles()[x][7].index()] = (x, 7);
+        }
+
+        Self::from_towers(board, towers, [[Rank::Normal; 8]; 2], first)
+    }
+
+    /// Starts a game from arbitrary tower squares and ranks, indexed by player
+    /// and then by tower colour.
+    pub fn from_towers(
+        board: Board,
+        towers: [[(usize, usize); 8]; 2],
+        ranks: [[Rank; 8]; 2],
+        first: Player,
+    ) -> Self {
+        Self {
+            board,
+            towers,
+            ranks,
+            to_move: first,
+            forced: None,
+            last_mover: None,
+            passes: Vec::new(),
+            deadlocked: false,
+        }
+    }
+
+    /// The opening on the standard board with the towers ranked by `ranks`,
+    /// for tests that need sumos from the first move.
+    #[cfg(test)]
+    pub fn ranked_opening(ranks: [[Rank; 8]; 2], first: Player) -> Self {
+        let opening = GameState::new(Board::new(), first);
+        GameState::from_towers(Board::new(), opening.towers, ranks, first)
+    }
+
+    /// Sets the colour forced on the side to move, for setting up positions.
+    /// A forced colour comes from the opponent's last move, so the opponent
+    /// loses if the passes that follow end in a deadlock.
+    pub fn set_forced(&mut self, forced: Option<Colors>) {
+        self.forced = forced;
+        self.last_mover = forced.map(|_| self.to_move.opponent());
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn to_move(&self) -> Player {
+        self.to_move
+    }
+
+    /// The colour of the tower that has to move next, `None` on the opening move.
+    pub fn forced(&self) -> Option<Colors> {
+        self.forced
+    }
+
+    /// The player who made the last move other than a pass.
+    pub fn last_mover(&self) -> Option<Player> {
+        self.last_mover
+    }
+
+    /// The side and tower colour of every pass since the last real move.
+    pub fn passes(&self) -> &[(Player, Colors)] {
+        &self.passes
+    }
+
+    pub fn tower(&self, player: Player, color: Colors) -> (usize, usize) {
+        self.towers[player as usize][color.index()]
+    }
+
+    pub fn rank(&self, player: Player, color: Colors) -> Rank {
+        self.ranks[player as usize][color.index()]
+    }
+
+    pub fn tower_at(&self, square: (usize, usize)) -> Option<(Player, Colors)> {
+        [Player::White, Player::Black]
+            .into_iter()
+            .find_map(|player| {
+                Colors::ALL
+                    .into_iter()
+                    .find(|&color| self.tower(player, color) == square)
+                    .map(|color| (player, color))
+            })
+    }
+
+    pub fn occupied(&self) -> [[bool; 8]; 8] {
+        let mut occupied = [[false; 8]; 8];
+        for &(x, y) in self.towers.iter().flatten() {
+            occupied[x][y] = true;
+        }
+        occupied
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        self.result().map(|(winner, _)| winner)
+    }
+
+    pub fn result(&self) -> Option<(Player, WinReason)> {
+        if self.towers[Player::White as usize]
+            .iter()
+            .any(|&(_, y)| y == 7)
+        {
+            Some((Player::White, WinReason::HomeRow))
+        } else if self.towers[Player::Black as usize]
+            .iter()
+            .any(|&(_, y)| y == 0)
+        {
+            Some((Player::Black, WinReason::HomeRow))
+        } else if self.deadlocked {
+            self.last_mover
+                .map(|loser| (loser.opponent(), WinReason::Deadlock))
+        } else {
+            None
+        }
+    }
+
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+
+        if self.winner().is_some() || self.deadlocked {
+            return moves;
+        }
+
+        let occupied = self.occupied();
+
+        match self.forced {
+            None => {
+                for color in Colors::ALL {
+                    let from = self.tower(self.to_move, color);
+                    let max = self.rank(self.to_move, color).max_distance();
+                    for to in ray_moves(from, self.to_move, max, &occupied) {
+                        moves.push(Move {
+                            from,
+                            to,
+                            kind: MoveKind::Normal,
+                        });
+                    }
+                }
+            }
+            Some(color) => {
+                let from = self.tower(self.to_move, color);
+                let max = self.rank(self.to_move, color).max_distance();
+                for to in ray_moves(from, self.to_move, max, &occupied) {
+                    moves.push(Move {
+                        from,
+                        to,
+                        kind: MoveKind::Normal,
+                    });
+                }
+
+                if let Some(to) = self.sumo_push(color) {
+                    moves.push(Move {
+                        from,
+                        to,
+                        kind: MoveKind::SumoPush,
+                    });
+                }
+
+                if moves.is_empty() {
+                    moves.push(Move {
+                        from,
+                        to: from,
+                        kind: MoveKind::Pass,
+                    });
+                }
+            }
+        }
+
+        moves
+    }
+
+    /// The square a sumo of the side to move steps onto when it can push the
+    /// line of towers straight in front of it. The line has to consist of lower
+    /// ranked opponent towers, no more than the sumo's push strength, with an
+    /// empty square behind it.
+    fn sumo_push(&self, color: Colors) -> Option<(usize, usize)> {
+        let player = self.to_move;
+        let rank = self.rank(player, color);
+        let (x, y) = self.tower(player, color);
+
+        let mut pushed = 0;
+        for distance in 1.. {
+            let square = (x, advance(player, y, distance)?);
+            match self.tower_at(square) {
+                None if pushed > 0 => return Some((x, advance(player, y, 1)?)),
+                Some((owner, other))
+                    if owner != player
+                        && self.rank(owner, other) < rank
+                        && pushed < rank.push_strength() =>
+                {
+                    pushed += 1
+                }
+                _ => return None,
+            }
+        }
+
+        None
+    }
+
+    /// Moves a tower of the side to move. A pass leaves the tower in place, so
+    /// the opponent is forced by the colour of the blocked tower's square. A
+    /// sumo push keeps the turn, forced by the colour of the square the last
+    /// pushed tower lands on.
+    ///
+    /// Passes since the last real move are remembered; once they bring the game
+    /// back to a side and colour that already passed, the towers are deadlocked
+    /// and whoever made that last real move loses.
+    pub fn apply(&mut self, mv: Move) {
+        let player = self.to_move;
+        let color = Colors::ALL
+            .into_iter()
+            .find(|&color| self.tower(player, color) == mv.from)
+            .unwrap();
+
+        match mv.kind {
+            MoveKind::Normal | MoveKind::SumoPush => {
+                self.last_mover = Some(player);
+                self.passes.clear();
+            }
+            MoveKind::Pass => self.passes.push((player, color)),
+        }
+
+        let mut forced_square = mv.to;
+
+        if mv.kind == MoveKind::SumoPush {
+            let opponent = player.opponent();
+            let mut pushed = Vec::new();
+            let mut square = Some(mv.to);
+
+            while let Some((x, y)) = square {
+                match self.tower_at((x, y)) {
+                    Some((owner, color)) if owner == opponent => {
+                        pushed.push(color);
+                        square = advance(player, y, 1).map(|y| (x, y));
+                    }
+                    _ => break,
+                }
+            }
+
+            for color in pushed {
+                let (x, y) = self.tower(opponent, color);
+                forced_square = (x, advance(player, y, 1).unwrap());
+                self.towers[opponent as usize][color.index()] = forced_square;
+            }
+        }
+
+        self.towers[player as usize][color.index()] = mv.to;
+        self.forced = Some(self.board.tiles()[forced_square.0][forced_square.1]);
+        if mv.kind != MoveKind::SumoPush {
+            self.to_move = player.opponent();
+        }
+
+        if mv.kind == MoveKind::Pass {
+            self.deadlocked = self.passes.contains(&(self.to_move, self.forced.unwrap()));
+        }
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        self.deadlocked || self.winner().is_some() || self.legal_moves().is_empty()
+    }
+}
+
+/// Walks the straight and both diagonal rays towards the opponent's home row,
+/// stopping at the first occupied square, the board edge or after `max` squares.
+fn ray_moves(
+    (x, y): (usize, usize),
+    player: Player,
+    max: usize,
+    occupied: &[[bool; 8]; 8],
+) -> Vec<(usize, usize)> {
+    let mut moves = Vec::new();
+
+    for dx in [0, -1, 1] {
+        for distance in 1..=max {
+            let y = match advance(player, y, distance) {
+                Some(y) => y,
+                None => break,
+            };
+            let x = match x.checked_add_signed(dx * distance as isize) {
+                Some(x) if x < 8 => x,
+                _ => break,
+            };
+
+            if occupied[x][y] {
+                break;
+            }
+            moves.push((x, y));
+        }
+    }
+
+    moves
+}
+
+/// The row `distance` squares towards the opponent's home row, if it is on the
+/// board.
+fn advance(player: Player, y: usize, distance: usize) -> Option<usize> {
+    match player {
+        Player::White => Some(y + distance).filter(|&y| y < 8),
+        Player::Black => y.checked_sub(distance),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::read_position;
+
+    /// White's green tower stands blocked in front of Black's home row and
+    /// Black's purple tower is about to move, the rest as in the opening.
+    fn blocked() -> GameState {
+        let mut towers = GameState::new(Board::new(), Player::White).towers;
+        towers[Player::White as usize][Colors::Green.index()] = (0, 6);
+        towers[Player::Black as usize][Colors::Purple.index()] = (7, 5);
+
+        let mut game =
+            GameState::from_towers(Board::new(), towers, [[Rank::Normal; 8]; 2], Player::Black);
+        game.set_forced(Some(Colors::Purple));
+        game
+    }
+
+    fn play_to(game: &mut GameState, to: (usize, usize)) {
+        let mv = game
+            .legal_moves()
+            .into_iter()
+            .find(|mv| mv.to == to)
+            .unwrap();
+        game.apply(mv);
+    }
+
+    /// Black's purple tower ends up blocked in front of White's home row on a
+    /// green square, so both blocked towers force each other in turn.
+    #[test]
+    fn deadlock_loses_for_the_last_mover() {
+        let mut game = blocked();
+        let mover = game.to_move();
+        play_to(&mut game, (3, 1));
+
+        while !game.is_terminal() {
+            let moves = game.legal_moves();
+            assert_eq!(moves.len(), 1);
+            assert_eq!(moves[0].kind, MoveKind::Pass);
+            game.apply(moves[0]);
+        }
+
+        assert_eq!(
+            game.passes(),
+            [
+                (Player::White, Colors::Green),
+                (Player::Black, Colors::Purple)
+            ]
+        );
+        assert_eq!(game.result(), Some((mover.opponent(), WinReason::Deadlock)));
+    }
+
+    /// On a green square with room ahead, Black's purple tower moves on after
+    /// White's pass.
+    #[test]
+    fn pass_to_a_free_tower_plays_on() {
+        let mut game = blocked();
+        play_to(&mut game, (5, 3));
+
+        let moves = game.legal_moves();
+        assert_eq!(
+            moves,
+            [Move {
+                from: (0, 6),
+                to: (0, 6),
+                kind: MoveKind::Pass,
+            }]
+        );
+        game.apply(moves[0]);
+
+        assert_eq!(game.forced(), Some(Colors::Purple));
+        assert_eq!(game.result(), None);
+        assert!(!game.is_terminal());
+        assert!(game
+            .legal_moves()
+            .iter()
+            .all(|mv| mv.kind == MoveKind::Normal));
+    }
+
+    fn push(game: &GameState) -> Option<Move> {
+        game.legal_moves()
+            .into_iter()
+            .find(|mv| mv.kind == MoveKind::SumoPush)
+    }
+
+    /// White's orange sumo on a3 faces Black's brown tower on a4, with a5
+    /// free behind it.
+    const PUSH: &str = "1grykpbo/8/8/8/n7/O+7/8/1BPKYRGN w o";
+
+    #[test]
+    fn sumo_push_moves_the_tower_back_one_square() {
+        let mut game = read_position(PUSH, Board::new()).unwrap();
+        let mv = push(&game).unwrap();
+        assert_eq!((mv.from, mv.to), ((0, 2), (0, 3)));
+
+        game.apply(mv);
+        assert_eq!(game.tower(Player::White, Colors::Orange), (0, 3));
+        assert_eq!(game.tower(Player::Black, Colors::Brown), (0, 4));
+        assert_eq!(game.tower_at((0, 5)), None);
+    }
+
+    /// The pusher lands on a pink square, the pushed tower on a yellow one.
+    #[test]
+    fn pusher_moves_again_forced_by_the_pushed_square() {
+        let mut game = read_position(PUSH, Board::new()).unwrap();
+        game.apply(push(&game).unwrap());
+
+        assert_eq!(game.to_move(), Player::White);
+        assert_eq!(game.forced(), Some(Colors::Yellow));
+        assert!(game
+            .legal_moves()
+            .iter()
+            .all(|mv| mv.from == game.tower(Player::White, Colors::Yellow)));
+    }
+
+    #[test]
+    fn sumo_pushes_only_weaker_towers() {
+        for black in ["n+", "n++"] {
+            let text = PUSH.replace("/n7/", &format!("/{}7/", black));
+            let game = read_position(&text, Board::new()).unwrap();
+            assert_eq!(push(&game), None, "{}", text);
+        }
+    }
+
+    /// Black's green tower on a5 backs up the brown one, which takes a double
+    /// sumo to push.
+    #[test]
+    fn sumo_pushes_as_many_towers_as_its_strength() {
+        let text = "2rykpbo/8/8/g7/n7/O+7/8/1BPKYRGN w o";
+        let game = read_position(text, Board::new()).unwrap();
+        assert_eq!(push(&game), None);
+
+        let mut game = read_position(&text.replace("O+", "O++"), Board::new()).unwrap();
+        game.apply(push(&game).unwrap());
+        assert_eq!(game.tower(Player::Black, Colors::Brown), (0, 4));
+        assert_eq!(game.tower(Player::Black, Colors::Green), (0, 5));
+        assert_eq!(game.forced(), Some(Colors::Blue));
+    }
+}
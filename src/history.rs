@@ -0,0 +1,245 @@
+use crate::{
+    game::{GameState, Move},
+    match_play::Fill,
+    notation,
+};
+
+/// The moves played in the match so far, round by round, with a cursor that
+/// undo and redo step back and forth within a round. Positions are rebuilt by
+/// replaying from the start of the round, which is cheap next to the length of
+/// a Kamisado game.
+///
+/// While playing, the cursor stays in the last round; only the replay viewer
+/// jumps it to earlier ones.
+pub struct History {
+    rounds: Vec<Round>,
+    round: usize,
+    ply: usize,
+}
+
+pub struct Round {
+    /// How the towers were lined up, `None` for the opening round.
+    pub fill: Option<Fill>,
+    pub start: GameState,
+    pub moves: Vec<Move>,
+}
+
+impl History {
+    pub fn new(start: GameState) -> Self {
+        Self {
+            rounds: vec![Round {
+                fill: None,
+                start,
+                moves: Vec::new(),
+            }],
+            round: 0,
+            ply: 0,
+        }
+    }
+
+    /// Every round so far, the last one being played. Its moves may run past
+    /// the cursor when some have been undone.
+    pub fn rounds(&self) -> &[Round] {
+        &self.rounds
+    }
+
+    /// The index of the round the cursor is in.
+    pub fn round(&self) -> usize {
+        self.round
+    }
+
+    /// How many moves of the current round have been played and not undone.
+    pub fn ply(&self) -> usize {
+        self.ply
+    }
+
+    /// The move that led to the position at the cursor.
+    pub fn last_move(&self) -> Option<Move> {
+        self.ply.checked_sub(1).map(|ply| self.current().moves[ply])
+    }
+
+    fn current(&self) -> &Round {
+        &self.rounds[self.round]
+    }
+
+    /// Moves the cursor to `ply` in `round`, or to the round's last move when
+    /// `ply` is past it, returning the position there.
+    pub fn jump(&mut self, round: usize, ply: usize) -> Option<GameState> {
+        let moves = self.rounds.get(round)?.moves.len();
+        self.round = round;
+        self.ply = ply.min(moves);
+        Some(self.position())
+    }
+
+    /// Closes the current round, dropping any undone moves, and starts the next
+    /// one from `start`.
+    pub fn next_round(&mut self, start: GameState, fill: Fill) {
+        let ply = self.ply;
+        self.rounds.last_mut().unwrap().moves.truncate(ply);
+        self.rounds.push(Round {
+            fill: Some(fill),
+            start,
+            moves: Vec::new(),
+        });
+        self.round = self.rounds.len() - 1;
+        self.ply = 0;
+    }
+
+    /// Records a move played at the cursor, dropping any undone moves after it.
+    pub fn push(&mut self, mv: Move) {
+        let ply = self.ply;
+        let moves = &mut self.rounds.last_mut().unwrap().moves;
+        moves.truncate(ply);
+        moves.push(mv);
+        self.ply += 1;
+    }
+
+    /// Steps back one move, returning the position before it.
+    pub fn undo(&mut self) -> Option<GameState> {
+        if self.ply == 0 {
+            return None;
+        }
+
+        self.ply -= 1;
+        Some(self.position())
+    }
+
+    /// Steps forward over an undone move, returning the position after it.
+    pub fn redo(&mut self) -> Option<GameState> {
+        if self.ply == self.current().moves.len() {
+            return None;
+        }
+
+        self.ply += 1;
+        Some(self.position())
+    }
+
+    /// The moves of the current round up to the cursor in notation, as played.
+    pub fn notation(&self) -> Vec<String> {
+        let round = self.current();
+        let mut game = round.start.clone();
+        round.moves[..self.ply]
+            .iter()
+            .map(|&mv| {
+                let text = notation::write_move(&game, mv);
+                game.apply(mv);
+                text
+            })
+            .collect()
+    }
+
+    fn position(&self) -> GameState {
+        let round = self.current();
+        let mut game = round.start.clone();
+        for &mv in round.moves[..self.ply].iter() {
+            game.apply(mv);
+        }
+        game
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{board::Board, game::Player, position::write_position};
+
+    /// Plays the first legal move `count` times, recording each one.
+    fn play(history: &mut History, game: &mut GameState, count: usize) {
+        for _ in 0..count {
+            let mv = game.legal_moves()[0];
+            game.apply(mv);
+            history.push(mv);
+        }
+    }
+
+    fn opening() -> GameState {
+        GameState::new(Board::new(), Player::White)
+    }
+
+    #[test]
+    fn undo_stops_at_the_start() {
+        let mut game = opening();
+        let mut history = History::new(game.clone());
+        play(&mut history, &mut game, 2);
+
+        history.undo().unwrap();
+        let start = history.undo().unwrap();
+        assert_eq!(write_position(&start), write_position(&opening()));
+        assert!(history.undo().is_none());
+        assert_eq!(history.ply(), 0);
+        assert_eq!(history.last_move(), None);
+
+        let after = history.redo().unwrap();
+        history.redo().unwrap();
+        assert!(history.redo().is_none());
+        assert_eq!(history.ply(), 2);
+        assert_eq!(history.rounds()[0].moves.len(), 2);
+
+        let mut first = opening();
+        first.apply(history.rounds()[0].moves[0]);
+        assert_eq!(write_position(&after), write_position(&first));
+    }
+
+    #[test]
+    fn a_new_move_drops_the_undone_ones() {
+        let mut game = opening();
+        let mut history = History::new(game.clone());
+        play(&mut history, &mut game, 3);
+
+        history.undo();
+        let mut game = history.undo().unwrap();
+        let mv = *game.legal_moves().last().unwrap();
+        game.apply(mv);
+        history.push(mv);
+
+        assert_eq!(history.ply(), 2);
+        assert_eq!(history.rounds()[0].moves.len(), 2);
+        assert_eq!(history.last_move(), Some(mv));
+        assert!(history.redo().is_none());
+    }
+
+    #[test]
+    fn undo_stays_in_the_round() {
+        let mut game = opening();
+        let mut history = History::new(game.clone());
+        play(&mut history, &mut game, 3);
+        history.undo();
+
+        let start = GameState::new(Board::new(), Player::Black);
+        history.next_round(start.clone(), Fill::Right);
+        assert_eq!(history.round(), 1);
+        assert_eq!(history.rounds()[0].moves.len(), 2);
+        assert_eq!(history.rounds()[1].fill, Some(Fill::Right));
+
+        let mut game = start;
+        play(&mut history, &mut game, 1);
+        history.undo().unwrap();
+        assert!(history.undo().is_none());
+        assert_eq!(history.round(), 1);
+        assert_eq!(history.ply(), 0);
+    }
+
+    #[test]
+    fn jump_moves_between_rounds() {
+        let mut game = opening();
+        let mut history = History::new(game.clone());
+        play(&mut history, &mut game, 4);
+        let end_of_first = write_position(&game);
+
+        let mut game = GameState::new(Board::new(), Player::Black);
+        history.next_round(game.clone(), Fill::Left);
+        play(&mut history, &mut game, 2);
+
+        let position = history.jump(0, 99).unwrap();
+        assert_eq!((history.round(), history.ply()), (0, 4));
+        assert_eq!(write_position(&position), end_of_first);
+        assert_eq!(history.notation().len(), 4);
+
+        let position = history.jump(1, 1).unwrap();
+        assert_eq!((history.round(), history.ply()), (1, 1));
+        assert_eq!(position.to_move(), Player::White);
+
+        assert!(history.jump(2, 0).is_none());
+        assert_eq!((history.round(), history.ply()), (1, 1));
+    }
+}
@@ -1,80 +1,214 @@
 #![allow(clippy::needless_range_loop)]
-use std::ops::{Deref, DerefMut};
+use std::{
+    sync::{
+        mpsc::{self, Receiver, TryRecvError},
+        Arc, Mutex,
+    },
+    thread,
+};
 
+use ai::Engine;
+use animation::{move_pieces, Animating};
 use bevy::{
+    ecs::schedule::State as AppState,
     input::Input,
+    log::{info, warn},
     math::{Vec2, Vec3},
     prelude::{
-        App, Color, Commands, Component, Entity, MouseButton, OrthographicCameraBundle, Query, Res,
-        ResMut, Transform, With, Without,
+        App, Color, Commands, Component, DespawnRecursiveExt, Entity, KeyCode, MouseButton,
+        OrthographicCameraBundle, ParallelSystemDescriptorCoercion, Query, Res, ResMut, SystemSet,
+        Transform, UiCameraBundle, With, Without,
     },
     sprite::{Sprite, SpriteBundle},
     window::Windows,
     DefaultPlugins,
 };
-use board::{create_board, Board};
+use board::{create_board, paint_board};
 use colors::Colors;
-use pieces::{create_pieces, BlackPiece, Piece, WhitePiece};
+use game::{GameState, Move, MoveKind, Player, WinReason};
+use history::History;
+use match_play::{Fill, Match};
+use menu::{
+    close_overlay, game_over_input, menu_input, setup_game_over, setup_menu, update_menu,
+    MenuCursor, Mode, Settings,
+};
+use pieces::{create_pieces, show_sumo_teeth, BlackPiece, Piece, WhitePiece};
+use record::Header;
+use ui::{setup_ui, update_history_panel, update_hud};
 
+mod ai;
+mod animation;
+mod bitboard;
 mod board;
 mod colors;
 mod config;
+mod game;
+mod history;
+mod match_play;
+mod menu;
+mod notation;
+mod perft;
 mod pieces;
+mod position;
+mod record;
+#[cfg(test)]
+mod test_support;
+mod ui;
+mod zobrist;
+
+/// The screens of the app. The game over screen is pushed over the finished
+/// match, which stays on the board behind it.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+enum Screen {
+    Menu,
+    Playing,
+    GameOver,
+    /// A new match with the same settings, passed straight through to
+    /// `Playing` so the game is torn down and set up afresh.
+    NewGame,
+}
+
+/// Everything spawned for a game, despawned when it is left for the menu.
+#[derive(Component)]
+struct InGame;
 
 enum State {
     Start(Option<Colors>),
     White(Colors),
     Black(Colors),
+    Won(Player),
+    /// Stepping through a saved record, with the live game put aside.
+    Replay,
 }
 
-struct PossibleWhiteMoves {
-    inner: Vec<(usize, usize)>,
+/// The moves on offer to the side to move, all made by the same tower once it
+/// is forced or picked on the opening move.
+struct LegalMoves {
+    player: Player,
+    forced: Option<Colors>,
+    moves: Vec<Move>,
 }
 
-impl Deref for PossibleWhiteMoves {
-    type Target = Vec<(usize, usize)>;
+impl LegalMoves {
+    fn new(player: Player) -> Self {
+        Self {
+            player,
+            forced: None,
+            moves: Vec::new(),
+        }
+    }
 
-    fn deref(&self) -> &Self::Target {
-        &self.inner
+    fn set(&mut self, player: Player, forced: Option<Colors>, moves: Vec<Move>) {
+        self.player = player;
+        self.forced = forced;
+        self.moves = moves;
     }
-}
 
-impl DerefMut for PossibleWhiteMoves {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.inner
+    fn clear(&mut self) {
+        self.forced = None;
+        self.moves.clear();
     }
-}
 
-struct PossibleBlackMoves {
-    inner: Vec<(usize, usize)>,
+    fn find(&self, from: (usize, usize), to: (usize, usize)) -> Option<Move> {
+        self.moves
+            .iter()
+            .copied()
+            .find(|mv| mv.from == from && mv.to == to)
+    }
 }
 
-impl Deref for PossibleBlackMoves {
-    type Target = Vec<(usize, usize)>;
-
-    fn deref(&self) -> &Self::Target {
-        &self.inner
-    }
+/// A computer player that searches on a background thread so the board keeps
+/// rendering while it thinks.
+struct Computer {
+    player: Player,
+    name: String,
+    engine: Arc<dyn Engine>,
+    thinking: Option<Mutex<Receiver<Option<Move>>>>,
 }
 
-impl DerefMut for PossibleBlackMoves {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.inner
+impl Computer {
+    fn new(player: Player, name: String, engine: Arc<dyn Engine>) -> Self {
+        Self {
+            player,
+            name,
+            engine,
+            thinking: None,
+        }
     }
 }
 
 fn main() {
+    // `--perft <depth>` counts the move tree from the opening instead of
+    // starting the game.
+    let mut args = std::env::args().skip(1);
+    if args.next().as_deref() == Some("--perft") {
+        let depth = args
+            .next()
+            .and_then(|depth| depth.parse().ok())
+            .unwrap_or(4);
+        perft::print(depth);
+        return;
+    }
+
+    // The game resources are set up again from the menu's settings on every
+    // start, these only stand in until then.
+    let settings = Settings::default();
+    let game = settings.game();
+
     App::new()
+        .insert_resource(History::new(game.clone()))
+        .insert_resource(Match::new(settings.length))
+        .insert_resource(LegalMoves::new(game.to_move()))
+        .insert_resource(game)
+        .insert_resource(settings)
+        .insert_resource(MenuCursor::default())
         .insert_resource(State::Start(None))
-        .insert_resource(PossibleWhiteMoves { inner: Vec::new() })
-        .insert_resource(PossibleBlackMoves { inner: Vec::new() })
+        .insert_resource(Animating::default())
         .add_plugins(DefaultPlugins)
+        .add_state(Screen::Menu)
         .add_startup_system(setup_camera)
-        .add_startup_system(create_board)
-        .add_startup_system(create_pieces)
-        .add_system(mouse_button_input)
-        .add_system(move_pieces)
-        .add_system(spawn_moves)
+        .add_system_set(SystemSet::on_enter(Screen::Menu).with_system(setup_menu))
+        .add_system_set(
+            SystemSet::on_update(Screen::Menu)
+                .with_system(menu_input)
+                .with_system(update_menu),
+        )
+        .add_system_set(SystemSet::on_exit(Screen::Menu).with_system(close_overlay))
+        .add_system_set(
+            SystemSet::on_enter(Screen::Playing)
+                .with_system(start_game.label("start_game"))
+                .with_system(create_board.after("start_game"))
+                .with_system(create_pieces.after("start_game"))
+                .with_system(setup_ui),
+        )
+        .add_system_set(
+            SystemSet::on_update(Screen::Playing)
+                .before("animation")
+                .with_system(mouse_button_input)
+                .with_system(computer_move)
+                .with_system(undo_redo)
+                .with_system(save_load)
+                .with_system(replay_viewer)
+                .with_system(next_round)
+                .with_system(leave_game),
+        )
+        .add_system_set(
+            SystemSet::on_update(Screen::Playing)
+                .with_system(move_pieces.label("animation"))
+                .with_system(show_sumo_teeth)
+                .with_system(paint_board)
+                .with_system(spawn_moves)
+                .with_system(spawn_banner)
+                .with_system(update_history_panel)
+                .with_system(update_hud)
+                .with_system(score_round)
+                .with_system(finish_match.after("animation")),
+        )
+        .add_system_set(SystemSet::on_exit(Screen::Playing).with_system(despawn_game))
+        .add_system_set(SystemSet::on_enter(Screen::GameOver).with_system(setup_game_over))
+        .add_system_set(SystemSet::on_update(Screen::GameOver).with_system(game_over_input))
+        .add_system_set(SystemSet::on_exit(Screen::GameOver).with_system(close_overlay))
+        .add_system_set(SystemSet::on_enter(Screen::NewGame).with_system(restart))
         .run();
 }
 
@@ -85,6 +219,81 @@ fn setup_camera(mut commands: Commands) {
     commands
         .spawn_bundle(OrthographicCameraBundle::new_2d())
         .insert(MainCamera);
+    commands.spawn_bundle(UiCameraBundle::default());
+}
+
+/// Sets up a new match from the menu's settings, replacing whatever game was
+/// played before.
+#[allow(clippy::too_many_arguments)]
+fn start_game(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut legal_moves: ResMut<LegalMoves>,
+    mut state: ResMut<State>,
+    mut game: ResMut<GameState>,
+    mut history: ResMut<History>,
+    mut match_play: ResMut<Match>,
+    mut animating: ResMut<Animating>,
+) {
+    *game = settings.game();
+    *history = History::new(game.clone());
+    *match_play = Match::new(settings.length);
+    *legal_moves = LegalMoves::new(game.to_move());
+    *state = State::Start(None);
+    update_turn(&game, &mut state, &mut legal_moves);
+    animating.0 = false;
+
+    match settings.mode {
+        Mode::VsComputer(player) => {
+            let engine = settings.engine.build(settings.strength);
+            let name = format!("Computer ({:?}, {:?})", settings.engine, settings.strength);
+            commands.insert_resource(Computer::new(player, name, engine));
+        }
+        Mode::HumanVsHuman | Mode::Network => commands.remove_resource::<Computer>(),
+    }
+    commands.remove_resource::<Viewer>();
+}
+
+/// Ctrl+N abandons the match for a new one with the same settings and Escape
+/// leaves it for the menu, except in the replay viewer where it goes back to
+/// the live game instead.
+fn leave_game(
+    mut keys: ResMut<Input<KeyCode>>,
+    viewer: Option<Res<Viewer>>,
+    mut screen: ResMut<AppState<Screen>>,
+) {
+    let control = keys.pressed(KeyCode::LControl) || keys.pressed(KeyCode::RControl);
+    let next = if control && keys.just_pressed(KeyCode::N) {
+        Screen::NewGame
+    } else if viewer.is_none() && keys.just_pressed(KeyCode::Escape) {
+        Screen::Menu
+    } else {
+        return;
+    };
+
+    screen.overwrite_replace(next).unwrap();
+    keys.clear();
+}
+
+fn restart(mut screen: ResMut<AppState<Screen>>) {
+    screen.set(Screen::Playing).unwrap();
+}
+
+/// Puts up the game over screen once the match is won and the last move has
+/// come to rest.
+fn finish_match(
+    match_play: Res<Match>,
+    animating: Res<Animating>,
+    mut screen: ResMut<AppState<Screen>>,
+) {
+    if match_play.winner().is_some() && !animating.0 {
+        // Leaving for the menu in the same frame takes precedence.
+        screen.push(Screen::GameOver).ok();
+    }
+}
+
+fn despawn_game(mut commands: Commands, entities: Query<Entity, With<InGame>>) {
+    entities.for_each(|entity| commands.entity(entity).despawn_recursive());
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -94,275 +303,607 @@ fn mouse_button_input(
     buttons: Res<Input<MouseButton>>,
     mut whites: Query<&mut Piece, (With<WhitePiece>, Without<BlackPiece>)>,
     mut blacks: Query<&mut Piece, (With<BlackPiece>, Without<WhitePiece>)>,
-    mut possible_white_moves: ResMut<PossibleWhiteMoves>,
-    mut possible_black_moves: ResMut<PossibleBlackMoves>,
-    state: ResMut<State>,
-    board: Res<Board>,
+    mut legal_moves: ResMut<LegalMoves>,
+    mut state: ResMut<State>,
+    mut game: ResMut<GameState>,
+    mut history: ResMut<History>,
+    computer: Option<Res<Computer>>,
+    animating: Res<Animating>,
 ) {
+    if animating.0 || computer.is_some_and(|computer| computer.player == game.to_move()) {
+        return;
+    }
+
     if buttons.just_pressed(MouseButton::Left) {
         let (x, y) = coords(windows, camera);
 
-        match state.as_ref() {
-            State::Start(_) if y == 0 => {
-                possible_white_moves.clear();
-
-                for i in 1..7 {
-                    possible_white_moves.push((x, i));
+        match (&*state, game.to_move()) {
+            (State::Won(_) | State::Replay, _) => {}
+            (State::Start(_), player) if y == player.home_row() => {
+                let color = match game.tower_at((x, y)) {
+                    Some((owner, color)) if owner == player => color,
+                    _ => return,
+                };
+                let moves = game
+                    .legal_moves()
+                    .into_iter()
+                    .filter(|mv| mv.from == (x, y))
+                    .collect();
+
+                legal_moves.set(player, Some(color), moves);
+                *state = State::Start(Some(color));
+            }
+            (State::Start(None), _) => {}
+            (&State::Start(Some(color)) | &State::White(color) | &State::Black(color), player) => {
+                if let Some(mv) = legal_moves.find(game.tower(player, color), (x, y)) {
+                    game.apply(mv);
+                    history.push(mv);
+                    sync_pieces(&game, &mut whites, &mut blacks);
+                    update_turn(&game, &mut state, &mut legal_moves);
                 }
+            }
+        }
+    }
+}
 
-                for i in 1..=x {
-                    if i == 7 {
-                        break;
-                    }
-                    possible_white_moves.push((x - i, i));
-                }
+/// Waits for the pieces to settle before picking up the search result, so a
+/// quick reply doesn't land while the player's move is still sliding.
+#[allow(clippy::too_many_arguments)]
+fn computer_move(
+    computer: Option<ResMut<Computer>>,
+    animating: Res<Animating>,
+    mut whites: Query<&mut Piece, (With<WhitePiece>, Without<BlackPiece>)>,
+    mut blacks: Query<&mut Piece, (With<BlackPiece>, Without<WhitePiece>)>,
+    mut legal_moves: ResMut<LegalMoves>,
+    mut state: ResMut<State>,
+    mut game: ResMut<GameState>,
+    mut history: ResMut<History>,
+) {
+    let mut computer = match computer {
+        Some(computer) => computer,
+        None => return,
+    };
+
+    if animating.0
+        || matches!(*state, State::Won(_) | State::Replay)
+        || computer.player != game.to_move()
+    {
+        return;
+    }
 
-                for i in (x + 1)..8 {
-                    if i - x == 7 {
-                        break;
-                    }
-                    possible_white_moves.push((i, i - x));
-                }
+    let received = match &computer.thinking {
+        Some(receiver) => receiver.lock().unwrap().try_recv(),
+        None => {
+            let (sender, receiver) = mpsc::channel();
+            let engine = computer.engine.clone();
+            let position = game.clone();
+            thread::spawn(move || sender.send(engine.choose(&position)));
+
+            computer.thinking = Some(Mutex::new(receiver));
+            return;
+        }
+    };
+
+    match received {
+        Ok(Some(mv)) => {
+            game.apply(mv);
+            history.push(mv);
+            sync_pieces(&game, &mut whites, &mut blacks);
+            update_turn(&game, &mut state, &mut legal_moves);
+        }
+        Ok(None) | Err(TryRecvError::Disconnected) => {}
+        Err(TryRecvError::Empty) => return,
+    }
+
+    computer.thinking = None;
+}
+
+/// Ctrl+Z takes back the last move and Ctrl+Y plays it again, until the round
+/// is won. Against the computer both skip over its moves, so the player is back
+/// on turn instead of watching it replay the same move.
+#[allow(clippy::too_many_arguments)]
+fn undo_redo(
+    keys: Res<Input<KeyCode>>,
+    computer: Option<ResMut<Computer>>,
+    mut whites: Query<&mut Piece, (With<WhitePiece>, Without<BlackPiece>)>,
+    mut blacks: Query<&mut Piece, (With<BlackPiece>, Without<WhitePiece>)>,
+    mut legal_moves: ResMut<LegalMoves>,
+    mut state: ResMut<State>,
+    mut game: ResMut<GameState>,
+    mut history: ResMut<History>,
+    animating: Res<Animating>,
+) {
+    if animating.0 || matches!(*state, State::Won(_) | State::Replay) {
+        return;
+    }
 
-                *state.into_inner() = State::Start(Some(board.tiles()[x][0]));
+    let control = keys.pressed(KeyCode::LControl) || keys.pressed(KeyCode::RControl);
+    let step = if control && keys.just_pressed(KeyCode::Z) {
+        History::undo
+    } else if control && keys.just_pressed(KeyCode::Y) {
+        History::redo
+    } else {
+        return;
+    };
+
+    let mut position = match step(&mut history) {
+        Some(position) => position,
+        None => return,
+    };
+
+    if let Some(mut computer) = computer {
+        while position.to_move() == computer.player {
+            match step(&mut history) {
+                Some(next) => position = next,
+                None => break,
             }
-            State::Start(None) => {}
-            State::Start(Some(color)) => {
-                if possible_white_moves.contains(&(x, y)) {
-                    let mut piece = whites
-                        .iter_mut()
-                        .find(|piece| piece.color() == *color)
-                        .unwrap();
-
-                    piece.move_piece(x, y);
-                    possible_white_moves.clear();
-
-                    let mut occupied = [[false; 8]; 8];
-                    for white in whites.iter() {
-                        occupied[white.x()][white.y()] = true;
-                    }
-                    for black in blacks.iter() {
-                        occupied[black.x()][black.y()] = true;
-                    }
-
-                    let black = blacks
-                        .iter()
-                        .find(|p| p.color() == board.tiles()[x][y])
-                        .unwrap();
-
-                    for i in (0..black.y()).rev() {
-                        if occupied[black.x()][i] {
-                            break;
-                        }
-                        possible_black_moves.push((black.x(), i))
-                    }
-
-                    for i in (0..black.x()).rev() {
-                        let y = black.y() - (black.x() - i);
-                        if occupied[i][y] {
-                            break;
-                        }
-                        possible_black_moves.push((i, y));
-                        if y == 0 {
-                            break;
-                        }
-                    }
-
-                    for i in (black.x() + 1)..8 {
-                        let y = black.y() - (i - black.x());
-                        if occupied[i][y] {
-                            break;
-                        }
-                        possible_black_moves.push((i, y));
-                        if y == 0 {
-                            break;
-                        }
-                    }
-
-                    if !possible_black_moves.is_empty() {
-                        *state.into_inner() = State::Black(black.color());
-                    }
-                }
+        }
+
+        // A search still running was started from a position that is gone.
+        computer.thinking = None;
+    }
+
+    *game = position;
+    sync_pieces(&game, &mut whites, &mut blacks);
+    update_turn(&game, &mut state, &mut legal_moves);
+}
+
+/// Ctrl+S writes the match so far to the record file and Ctrl+O replays the
+/// one saved there, picking up where it left off. Ctrl+P logs the position
+/// string of the board, for sharing it.
+#[allow(clippy::too_many_arguments)]
+fn save_load(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    computer: Option<ResMut<Computer>>,
+    mut whites: Query<&mut Piece, (With<WhitePiece>, Without<BlackPiece>)>,
+    mut blacks: Query<&mut Piece, (With<BlackPiece>, Without<WhitePiece>)>,
+    mut legal_moves: ResMut<LegalMoves>,
+    mut state: ResMut<State>,
+    mut game: ResMut<GameState>,
+    mut history: ResMut<History>,
+    mut match_play: ResMut<Match>,
+    banners: Query<Entity, With<Banner>>,
+) {
+    if !(keys.pressed(KeyCode::LControl) || keys.pressed(KeyCode::RControl)) {
+        return;
+    }
+
+    let replaying = matches!(*state, State::Replay);
+
+    if keys.just_pressed(KeyCode::S) && !replaying {
+        let name = |player| match &computer {
+            Some(computer) if computer.player == player => computer.name.clone(),
+            _ => "Human".to_string(),
+        };
+        let header = Header {
+            white: name(Player::White),
+            black: name(Player::Black),
+            date: record::today(),
+            length: match_play.length(),
+            first: history.rounds()[0].start.to_move(),
+            layout: game.board().layout(),
+        };
+
+        match record::save(config::RECORD_FILE, &header, &history) {
+            Ok(()) => info!("Saved the match to {}", config::RECORD_FILE),
+            Err(error) => warn!("Could not save {}: {}", config::RECORD_FILE, error),
+        }
+    } else if keys.just_pressed(KeyCode::P) {
+        info!("Position: {}", position::write_position(&game));
+    } else if keys.just_pressed(KeyCode::O) && !replaying {
+        let loaded = match record::load(config::RECORD_FILE) {
+            Ok(loaded) => loaded,
+            Err(error) => {
+                warn!("Could not load {}: {}", config::RECORD_FILE, error);
+                return;
             }
-            State::White(color) => {
-                if possible_white_moves.contains(&(x, y)) {
-                    let mut piece = whites
-                        .iter_mut()
-                        .find(|piece| piece.color() == *color)
-                        .unwrap();
-
-                    piece.move_piece(x, y);
-                    possible_white_moves.clear();
-
-                    let mut occupied = [[false; 8]; 8];
-                    for white in whites.iter() {
-                        occupied[white.x()][white.y()] = true;
-                    }
-                    for black in blacks.iter() {
-                        occupied[black.x()][black.y()] = true;
-                    }
-
-                    let black = blacks
-                        .iter()
-                        .find(|p| p.color() == board.tiles()[x][y])
-                        .unwrap();
-
-                    for i in (0..black.y()).rev() {
-                        if occupied[black.x()][i] {
-                            break;
-                        }
-                        possible_black_moves.push((black.x(), i))
-                    }
-
-                    for i in (0..black.x()).rev() {
-                        let y = black.y() - (black.x() - i);
-                        if occupied[i][y] {
-                            break;
-                        }
-                        possible_black_moves.push((i, y));
-                        if y == 0 {
-                            break;
-                        }
-                    }
-
-                    for i in (black.x() + 1)..8 {
-                        let y = black.y() - (i - black.x());
-                        if occupied[i][y] {
-                            break;
-                        }
-                        possible_black_moves.push((i, y));
-                        if y == 0 {
-                            break;
-                        }
-                    }
-
-                    if !possible_black_moves.is_empty() {
-                        *state.into_inner() = State::Black(black.color());
-                    }
-                }
+        };
+
+        info!(
+            "Loaded {} vs {} from {}",
+            loaded.header.white, loaded.header.black, loaded.header.date
+        );
+
+        *game = loaded.game;
+        *history = loaded.history;
+        *match_play = loaded.match_play;
+
+        if let Some(mut computer) = computer {
+            computer.thinking = None;
+        }
+
+        sync_pieces(&game, &mut whites, &mut blacks);
+        banners.for_each(|entity| commands.entity(entity).despawn());
+        *state = State::Start(None);
+        update_turn(&game, &mut state, &mut legal_moves);
+    }
+}
+
+/// The live game and its history, put aside while a record is replayed.
+struct Viewer {
+    game: GameState,
+    history: History,
+    /// Digits typed so far for a ply to jump to.
+    typed: usize,
+}
+
+/// Ctrl+R opens the record file in the replay viewer. The arrow keys step back
+/// and forth through the moves, Up and Down switch to the next or previous
+/// round, Home and End go to either end of the round and a ply number followed
+/// by Enter jumps to it. Escape returns to the live game.
+#[allow(clippy::too_many_arguments)]
+fn replay_viewer(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    viewer: Option<ResMut<Viewer>>,
+    computer: Option<ResMut<Computer>>,
+    mut whites: Query<&mut Piece, (With<WhitePiece>, Without<BlackPiece>)>,
+    mut blacks: Query<&mut Piece, (With<BlackPiece>, Without<WhitePiece>)>,
+    mut legal_moves: ResMut<LegalMoves>,
+    mut state: ResMut<State>,
+    mut game: ResMut<GameState>,
+    mut history: ResMut<History>,
+    banners: Query<Entity, With<Banner>>,
+    animating: Res<Animating>,
+) {
+    let mut viewer = match viewer {
+        Some(viewer) => viewer,
+        None => {
+            let control = keys.pressed(KeyCode::LControl) || keys.pressed(KeyCode::RControl);
+            if !control || !keys.just_pressed(KeyCode::R) {
+                return;
             }
-            State::Black(color) => {
-                if possible_black_moves.contains(&(x, y)) {
-                    let mut piece = blacks.iter_mut().find(|p| p.color() == *color).unwrap();
-
-                    piece.move_piece(x, y);
-                    possible_black_moves.clear();
-
-                    let mut occupied = [[false; 8]; 8];
-                    for white in whites.iter() {
-                        occupied[white.x()][white.y()] = true;
-                    }
-                    for black in blacks.iter() {
-                        occupied[black.x()][black.y()] = true;
-                    }
-
-                    let white = whites
-                        .iter()
-                        .find(|p| p.color() == board.tiles()[x][y])
-                        .unwrap();
-
-                    for i in (white.y() + 1)..8 {
-                        if occupied[white.x()][i] {
-                            break;
-                        }
-                        possible_white_moves.push((white.x(), i));
-                    }
-
-                    for i in (0..white.x()).rev() {
-                        let y = white.y() + white.x() - i;
-                        if occupied[i][y] {
-                            break;
-                        }
-                        possible_white_moves.push((i, y));
-                        if y == 7 {
-                            break;
-                        }
-                    }
-
-                    for i in (white.x() + 1)..8 {
-                        let y = white.y() + i - white.x();
-                        if occupied[i][y] {
-                            break;
-                        }
-                        possible_white_moves.push((i, y));
-                        if y == 7 {
-                            break;
-                        }
-                    }
-
-                    if !possible_white_moves.is_empty() {
-                        *state.into_inner() = State::White(white.color());
-                    }
+
+            let mut record = match record::load(config::RECORD_FILE) {
+                Ok(record) => record,
+                Err(error) => {
+                    warn!("Could not load {}: {}", config::RECORD_FILE, error);
+                    return;
                 }
+            };
+
+            info!(
+                "Replaying {} vs {} from {}",
+                record.header.white, record.header.black, record.header.date
+            );
+
+            if let Some(mut computer) = computer {
+                computer.thinking = None;
             }
+
+            let start = record.history.jump(0, 0).unwrap();
+            commands.insert_resource(Viewer {
+                game: std::mem::replace(&mut *game, start),
+                history: std::mem::replace(&mut *history, record.history),
+                typed: 0,
+            });
+
+            *state = State::Replay;
+            legal_moves.clear();
+            banners.for_each(|entity| commands.entity(entity).despawn());
+            sync_pieces(&game, &mut whites, &mut blacks);
+            return;
         }
+    };
+
+    if keys.just_pressed(KeyCode::Escape) {
+        let placeholder = History::new(viewer.game.clone());
+        *game = viewer.game.clone();
+        *history = std::mem::replace(&mut viewer.history, placeholder);
+        commands.remove_resource::<Viewer>();
+
+        sync_pieces(&game, &mut whites, &mut blacks);
+        update_turn(&game, &mut state, &mut legal_moves);
+        return;
+    }
+
+    if animating.0 {
+        return;
+    }
+
+    const DIGITS: [(KeyCode, KeyCode); 10] = [
+        (KeyCode::Key0, KeyCode::Numpad0),
+        (KeyCode::Key1, KeyCode::Numpad1),
+        (KeyCode::Key2, KeyCode::Numpad2),
+        (KeyCode::Key3, KeyCode::Numpad3),
+        (KeyCode::Key4, KeyCode::Numpad4),
+        (KeyCode::Key5, KeyCode::Numpad5),
+        (KeyCode::Key6, KeyCode::Numpad6),
+        (KeyCode::Key7, KeyCode::Numpad7),
+        (KeyCode::Key8, KeyCode::Numpad8),
+        (KeyCode::Key9, KeyCode::Numpad9),
+    ];
+
+    if let Some(digit) = DIGITS
+        .iter()
+        .position(|&(key, numpad)| keys.just_pressed(key) || keys.just_pressed(numpad))
+    {
+        viewer.typed = (viewer.typed * 10 + digit).min(999);
+        return;
+    }
+
+    let round = history.round();
+    let position = if keys.just_pressed(KeyCode::Right) {
+        history.redo()
+    } else if keys.just_pressed(KeyCode::Left) {
+        history.undo()
+    } else if keys.just_pressed(KeyCode::Up) {
+        history.jump(round + 1, 0)
+    } else if keys.just_pressed(KeyCode::Down) {
+        round
+            .checked_sub(1)
+            .and_then(|round| history.jump(round, 0))
+    } else if keys.just_pressed(KeyCode::Home) {
+        history.jump(round, 0)
+    } else if keys.just_pressed(KeyCode::End) {
+        history.jump(round, usize::MAX)
+    } else if keys.just_pressed(KeyCode::Return) || keys.just_pressed(KeyCode::NumpadEnter) {
+        let ply = std::mem::take(&mut viewer.typed);
+        history.jump(round, ply)
+    } else {
+        None
+    };
+
+    if let Some(position) = position {
+        *game = position;
+        sync_pieces(&game, &mut whites, &mut blacks);
+    }
+}
+
+/// Hands the turn to whoever moves next, which after a sumo push is the same
+/// player again. Undoing back to the opening move leaves the tower to pick.
+fn update_turn(game: &GameState, state: &mut State, legal_moves: &mut LegalMoves) {
+    if game.forced().is_none() {
+        legal_moves.clear();
+        *state = State::Start(None);
+        return;
+    }
+
+    legal_moves.set(game.to_move(), game.forced(), game.legal_moves());
+
+    if let Some(winner) = game.winner() {
+        *state = State::Won(winner);
+    } else if !game.is_terminal() {
+        *state = match game.to_move() {
+            Player::White => State::White(game.forced().unwrap()),
+            Player::Black => State::Black(game.forced().unwrap()),
+        };
+    }
+}
+
+fn sync_pieces(
+    game: &GameState,
+    whites: &mut Query<&mut Piece, (With<WhitePiece>, Without<BlackPiece>)>,
+    blacks: &mut Query<&mut Piece, (With<BlackPiece>, Without<WhitePiece>)>,
+) {
+    for mut piece in whites.iter_mut() {
+        let color = piece.color();
+        let (x, y) = game.tower(Player::White, color);
+        piece.move_piece(x, y);
+        piece.set_rank(game.rank(Player::White, color));
+    }
+
+    for mut piece in blacks.iter_mut() {
+        let color = piece.color();
+        let (x, y) = game.tower(Player::Black, color);
+        piece.move_piece(x, y);
+        piece.set_rank(game.rank(Player::Black, color));
     }
 }
 
 #[derive(Component)]
 struct PossibleMove;
 
+/// Marks the squares of the moves on offer, the tower that has to move and the
+/// squares of the last move. A blocked tower that can only pass gets a red
+/// cover instead, which is easy to miss with the usual marker underneath it.
 fn spawn_moves(
     mut commands: Commands,
-    possible_white_moves: Res<PossibleWhiteMoves>,
-    possible_black_moves: Res<PossibleBlackMoves>,
+    legal_moves: Res<LegalMoves>,
+    game: Res<GameState>,
+    history: Res<History>,
     entities: Query<Entity, With<PossibleMove>>,
 ) {
-    if possible_white_moves.is_changed() || possible_black_moves.is_changed() {
-        entities.for_each(|entity| commands.entity(entity).despawn());
+    if !legal_moves.is_changed() && !game.is_changed() {
+        return;
+    }
 
-        let start = -config::BOARD_SIZE / 2.0 + config::TILE_SIZE / 2.0;
-        let size = config::TILE_SIZE / 1.5;
+    entities.for_each(|entity| commands.entity(entity).despawn());
 
-        for &(x, y) in possible_white_moves.iter() {
-            commands
-                .spawn_bundle(SpriteBundle {
-                    sprite: Sprite {
-                        color: Color::rgba(1.0, 1.0, 1.0, 0.5),
-                        ..Default::default()
-                    },
-                    transform: Transform {
-                        translation: Vec3::new(
-                            start + config::TILE_SIZE * x as f32,
-                            start + config::TILE_SIZE * y as f32,
-                            0.0,
-                        ),
-                        scale: Vec3::new(size, size, 0.0),
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                })
-                .insert(PossibleMove);
+    let mut spawn = |square: (usize, usize), color: Color, size: f32, z: f32| {
+        commands
+            .spawn_bundle(square_sprite(square, color, size, z))
+            .insert(PossibleMove)
+            .insert(InGame);
+    };
+
+    if let Some(mv) = history.last_move() {
+        let color = Color::rgba(0.2, 0.6, 1.0, 0.45);
+        spawn(mv.from, color, config::TILE_SIZE, 0.1);
+        spawn(mv.to, color, config::TILE_SIZE, 0.1);
+    }
+
+    let color = match legal_moves.player {
+        Player::White => Color::rgba(1.0, 1.0, 1.0, 0.5),
+        Player::Black => Color::rgba(0.0, 0.0, 0.0, 0.8),
+    };
+    for mv in legal_moves.moves.iter() {
+        spawn(mv.to, color, config::TILE_SIZE / 1.5, 0.2);
+    }
+
+    // The forced tower, or the one picked on the opening move, sits on a gold
+    // square with a dark rim that shows around the piece.
+    if let Some(forced) = game.forced().or(legal_moves.forced) {
+        if game.winner().is_none() {
+            let square = game.tower(game.to_move(), forced);
+            spawn(
+                square,
+                Color::rgb(0.1, 0.1, 0.1),
+                config::TILE_SIZE * 0.9,
+                0.3,
+            );
+            spawn(
+                square,
+                Color::rgb(1.0, 0.8, 0.1),
+                config::TILE_SIZE * 0.8,
+                0.35,
+            );
         }
+    }
 
-        for &(x, y) in possible_black_moves.iter() {
-            commands
-                .spawn_bundle(SpriteBundle {
-                    sprite: Sprite {
-                        color: Color::rgba(0.0, 0.0, 0.0, 0.8),
-                        ..Default::default()
-                    },
-                    transform: Transform {
-                        translation: Vec3::new(
-                            start + config::TILE_SIZE * x as f32,
-                            start + config::TILE_SIZE * y as f32,
-                            0.0,
-                        ),
-                        scale: Vec3::new(size, size, 0.0),
+    if let Some(mv) = legal_moves
+        .moves
+        .iter()
+        .find(|mv| mv.kind == MoveKind::Pass)
+    {
+        spawn(
+            mv.from,
+            Color::rgba(0.9, 0.1, 0.1, 0.4),
+            config::TILE_SIZE,
+            1.0,
+        );
+    }
+}
+
+/// A plain square of `size` centred on a board square.
+fn square_sprite((x, y): (usize, usize), color: Color, size: f32, z: f32) -> SpriteBundle {
+    let start = -config::BOARD_SIZE / 2.0 + config::TILE_SIZE / 2.0;
+
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            ..Default::default()
+        },
+        transform: Transform {
+            translation: Vec3::new(
+                start + config::TILE_SIZE * x as f32,
+                start + config::TILE_SIZE * y as f32,
+                z,
+            ),
+            scale: Vec3::new(size, size, 0.0),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[derive(Component)]
+struct Banner;
+
+fn spawn_banner(mut commands: Commands, state: Res<State>, game: Res<GameState>) {
+    if state.is_changed() {
+        if let State::Won(winner) = *state {
+            let (outer, inner) = match winner {
+                Player::White => (Color::rgb(0.0, 0.0, 0.0), Color::rgb(1.0, 1.0, 1.0)),
+                Player::Black => (Color::rgb(1.0, 1.0, 1.0), Color::rgb(0.0, 0.0, 0.0)),
+            };
+
+            let mut bands = vec![
+                (outer, config::TILE_SIZE * 1.5, 1.0),
+                (inner, config::TILE_SIZE, 2.0),
+            ];
+
+            if let Some((_, reason)) = game.result() {
+                info!("{:?} wins: {}", winner, reason.description());
+
+                // A deadlock win is marked with a red stripe through the banner.
+                if reason == WinReason::Deadlock {
+                    bands.push((Color::rgb(0.9, 0.1, 0.1), config::TILE_SIZE / 4.0, 3.0));
+                }
+            }
+
+            for (color, height, z) in bands {
+                commands
+                    .spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            color,
+                            ..Default::default()
+                        },
+                        transform: Transform {
+                            translation: Vec3::new(0.0, 0.0, z),
+                            scale: Vec3::new(config::BOARD_SIZE, height, 0.0),
+                            ..Default::default()
+                        },
                         ..Default::default()
-                    },
-                    ..Default::default()
-                })
-                .insert(PossibleMove);
+                    })
+                    .insert(Banner)
+                    .insert(InGame);
+            }
         }
     }
 }
 
+fn score_round(
+    state: Res<State>,
+    game: Res<GameState>,
+    history: Res<History>,
+    mut match_play: ResMut<Match>,
+) {
+    if state.is_changed() {
+        if let State::Won(_) = *state {
+            if !match_play.score_round(&game) {
+                return;
+            }
+
+            info!("Moves: {}", history.notation().join(", "));
+
+            info!(
+                "Round {}: White {} - Black {}",
+                match_play.round(),
+                match_play.score(Player::White),
+                match_play.score(Player::Black)
+            );
+
+            if let Some(winner) = match_play.winner() {
+                info!("{:?} wins the match", winner);
+            }
+        }
+    }
+}
+
+/// Once a round is won and the match goes on, the arrow keys choose whether the
+/// towers fill their home rows from the left or from the right.
+#[allow(clippy::too_many_arguments)]
+fn next_round(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    mut whites: Query<&mut Piece, (With<WhitePiece>, Without<BlackPiece>)>,
+    mut blacks: Query<&mut Piece, (With<BlackPiece>, Without<WhitePiece>)>,
+    mut legal_moves: ResMut<LegalMoves>,
+    mut state: ResMut<State>,
+    mut game: ResMut<GameState>,
+    mut match_play: ResMut<Match>,
+    mut history: ResMut<History>,
+    banners: Query<Entity, With<Banner>>,
+    animating: Res<Animating>,
+) {
+    if animating.0 || !matches!(*state, State::Won(_)) || match_play.winner().is_some() {
+        return;
+    }
+
+    let fill = if keys.just_pressed(KeyCode::Left) {
+        Fill::Left
+    } else if keys.just_pressed(KeyCode::Right) {
+        Fill::Right
+    } else {
+        return;
+    };
+
+    *game = match_play.next_round(&game, fill);
+    history.next_round(game.clone(), fill);
+    sync_pieces(&game, &mut whites, &mut blacks);
+    legal_moves.clear();
+    banners.for_each(|entity| commands.entity(entity).despawn());
+
+    *state = State::Start(None);
+}
+
 fn coords(windows: Res<Windows>, camera: Query<&Transform, With<MainCamera>>) -> (usize, usize) {
     let window = windows.get_primary().unwrap();
 
     let pos = window.cursor_position().unwrap();
-    let size = Vec2::new(window.width() as f32, window.height() as f32);
+    let size = Vec2::new(window.width(), window.height());
     let p = pos - size / 2.0;
     let camera_transform = camera.single();
     let pos_wld = camera_transform.compute_matrix() * p.extend(0.0).extend(1.0);
@@ -388,12 +929,3 @@ fn coords(windows: Res<Windows>, camera: Query<&Transform, With<MainCamera>>) ->
 
     (x, y)
 }
-
-fn move_pieces(mut pieces: Query<(&Piece, &mut Transform)>) {
-    let start = -config::BOARD_SIZE / 2.0 + config::TILE_SIZE / 2.0;
-
-    for (piece, mut transform) in pieces.iter_mut() {
-        transform.translation.x = start + config::TILE_SIZE * piece.x() as f32;
-        transform.translation.y = start + config::TILE_SIZE * piece.y() as f32;
-    }
-}
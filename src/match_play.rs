@@ -0,0 +1,231 @@
+use crate::{
+    colors::Colors,
+    game::{GameState, Player, Rank, WinReason},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MatchLength {
+    Single,
+    Standard,
+    Long,
+    Marathon,
+    Custom(u32),
+}
+
+impl MatchLength {
+    /// The score needed to win the match.
+    pub fn points(&self) -> u32 {
+        match *self {
+            MatchLength::Single => 1,
+            MatchLength::Standard => 3,
+            MatchLength::Long => 7,
+            MatchLength::Marathon => 15,
+            MatchLength::Custom(points) => points,
+        }
+    }
+}
+
+/// How the towers are lined up on the home rows before the next round, seen
+/// from each player's own side of the board.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Fill {
+    Left,
+    Right,
+}
+
+pub struct Match {
+    length: MatchLength,
+    scores: [u32; 2],
+    ranks: [[Rank; 8]; 2],
+    round: u32,
+    scored: bool,
+}
+
+impl Match {
+    pub fn new(length: MatchLength) -> Self {
+        Self {
+            length,
+            scores: [0; 2],
+            ranks: [[Rank::Normal; 8]; 2],
+            round: 1,
+            scored: false,
+        }
+    }
+
+    pub fn length(&self) -> MatchLength {
+        self.length
+    }
+
+    pub fn score(&self, player: Player) -> u32 {
+        self.scores[player as usize]
+    }
+
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        [Player::White, Player::Black]
+            .into_iter()
+            .find(|&player| self.score(player) >= self.length.points())
+    }
+
+    /// Awards the points for a finished round. A tower that reaches the home
+    /// row scores according to its rank and is promoted to the next sumo rank,
+    /// a deadlock is worth a single point. Only the first call in a round
+    /// counts, so coming back to a won round doesn't score it again; the
+    /// return value says whether this call did.
+    pub fn score_round(&mut self, game: &GameState) -> bool {
+        let (winner, reason) = match game.result() {
+            Some(result) if !self.scored => result,
+            _ => return false,
+        };
+        self.scored = true;
+
+        let points = match reason {
+            WinReason::HomeRow => {
+                let color = Colors::ALL
+                    .into_iter()
+                    .find(|&color| game.tower(winner, color).1 == winner.opponent().home_row())
+                    .unwrap();
+                let rank = &mut self.ranks[winner as usize][color.index()];
+                let points = rank.points();
+                *rank = rank.promoted();
+                points
+            }
+            WinReason::Deadlock => 1,
+        };
+
+        self.scores[winner as usize] += points;
+        true
+    }
+
+    /// Lines the towers of a finished round back up on their home rows. Each
+    /// side fills its home row from `fill`, taking the towers nearest to home
+    /// first and, within a row, those nearest the filling side. The loser of
+    /// the round opens the next one.
+    pub fn next_round(&mut self, game: &GameState, fill: Fill) -> GameState {
+        let mut towers = [[(0, 0); 8]; 2];
+
+        for player in [Player::White, Player::Black] {
+            let home = player.home_row();
+            let from_low_x = matches!(
+                (fill, player),
+                (Fill::Left, Player::White) | (Fill::Right, Player::Black)
+            );
+            let column = |x: usize| if from_low_x { x } else { 7 - x };
+
+            let mut order = Colors::ALL;
+            order.sort_by_key(|&color| {
+                let (x, y) = game.tower(player, color);
+                (y.abs_diff(home), column(x))
+            });
+
+            for (i, color) in order.into_iter().enumerate() {
+                towers[player as usize][color.index()] = (column(i), home);
+            }
+        }
+
+        self.round += 1;
+        self.scored = false;
+
+        let loser = game.winner().unwrap().opponent();
+        GameState::from_towers(game.board().clone(), towers, self.ranks, loser)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        board::Board,
+        position::{read_position, write_position},
+    };
+
+    /// White's orange tower has reached a8, with the other towers spread over
+    /// the board at different distances from their home rows.
+    const WHITE_WINS: &str = "Og3n2/2y3r1/4k3/2N1G3/6Rp/b2o4/1Y3K2/2P4B b -";
+
+    #[test]
+    fn scores_by_rank_and_promotes() {
+        let game = read_position(WHITE_WINS, Board::new()).unwrap();
+        let orange = Colors::Orange.index();
+
+        for (rank, points, promoted) in [
+            (Rank::Normal, 1, Rank::Sumo),
+            (Rank::Sumo, 3, Rank::DoubleSumo),
+            (Rank::DoubleSumo, 7, Rank::TripleSumo),
+            (Rank::TripleSumo, 15, Rank::QuadrupleSumo),
+            (Rank::QuadrupleSumo, 15, Rank::QuadrupleSumo),
+        ] {
+            let mut match_play = Match::new(MatchLength::Marathon);
+            match_play.ranks[Player::White as usize][orange] = rank;
+
+            assert!(match_play.score_round(&game));
+            assert_eq!(match_play.score(Player::White), points);
+            assert_eq!(match_play.score(Player::Black), 0);
+            assert_eq!(match_play.ranks[Player::White as usize][orange], promoted);
+
+            assert!(!match_play.score_round(&game));
+            assert_eq!(match_play.score(Player::White), points);
+            assert_eq!(match_play.ranks[Player::White as usize][orange], promoted);
+        }
+    }
+
+    /// A deadlock is worth a single point whatever the ranks, and promotes
+    /// nothing.
+    #[test]
+    fn deadlock_scores_one_point() {
+        let mut game =
+            read_position("n4pb1/8/rB2Y3/O3yk1N/3ogP2/8/2K5/5RG1 w o", Board::new()).unwrap();
+        while !game.is_terminal() {
+            game.apply(game.legal_moves()[0]);
+        }
+        assert_eq!(game.result(), Some((Player::White, WinReason::Deadlock)));
+
+        let mut match_play = Match::new(MatchLength::Standard);
+        match_play.ranks = [[Rank::TripleSumo; 8]; 2];
+
+        assert!(match_play.score_round(&game));
+        assert_eq!(match_play.score(Player::White), 1);
+        assert_eq!(match_play.score(Player::Black), 0);
+        assert_eq!(match_play.ranks, [[Rank::TripleSumo; 8]; 2]);
+    }
+
+    /// Towers nearest home go first, ties broken from the filling side, which
+    /// for Black is the h file on Left. The winning tower keeps its promotion.
+    #[test]
+    fn fills_home_rows() {
+        let game = read_position(WHITE_WINS, Board::new()).unwrap();
+
+        for (fill, expected) in [
+            (Fill::Left, "bopkyrgn/8/8/8/8/8/8/PBYKRNGO+ b -"),
+            (Fill::Right, "gnyrkpbo/8/8/8/8/8/8/O+NGRYKPB b -"),
+        ] {
+            let mut match_play = Match::new(MatchLength::Standard);
+            match_play.score_round(&game);
+            let next = match_play.next_round(&game, fill);
+
+            assert_eq!(write_position(&next), expected);
+            assert_eq!(match_play.round(), 2);
+        }
+    }
+
+    #[test]
+    fn loser_opens_the_next_round() {
+        for (text, loser) in [
+            (WHITE_WINS, Player::Black),
+            ("ngrykpb1/8/8/8/8/8/7N/OBPKYRGo w -", Player::White),
+        ] {
+            let game = read_position(text, Board::new()).unwrap();
+            assert_eq!(game.winner(), Some(loser.opponent()));
+
+            let mut match_play = Match::new(MatchLength::Standard);
+            match_play.score_round(&game);
+            let next = match_play.next_round(&game, Fill::Left);
+
+            assert_eq!(next.to_move(), loser);
+            assert_eq!(next.forced(), None);
+        }
+    }
+}
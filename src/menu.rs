@@ -0,0 +1,382 @@
+use bevy::{
+    ecs::schedule::State as AppState,
+    input::Input,
+    math::{Rect, Size},
+    prelude::{
+        AssetServer, BuildChildren, Color, Commands, Component, DespawnRecursiveExt, Entity,
+        Handle, KeyCode, NodeBundle, Query, Res, ResMut, TextBundle, With,
+    },
+    text::{Font, Text, TextAlignment, TextSection, TextStyle},
+    ui::{AlignItems, JustifyContent, Style, UiColor, Val},
+};
+
+use crate::{
+    ai::{EngineKind, Strength},
+    animation::Easing,
+    board::{Board, Layout},
+    config,
+    game::{GameState, Player},
+    match_play::{Match, MatchLength},
+    position, Screen,
+};
+
+/// Who sits at the board.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mode {
+    HumanVsHuman,
+    /// Playing the computer, which takes the given side.
+    VsComputer(Player),
+    /// Playing someone on another machine. There is no transport yet, so the
+    /// menu lists it but won't start it.
+    Network,
+}
+
+impl Mode {
+    const ALL: [Mode; 4] = [
+        Mode::HumanVsHuman,
+        Mode::VsComputer(Player::Black),
+        Mode::VsComputer(Player::White),
+        Mode::Network,
+    ];
+}
+
+/// The choices made on the menu, which the next game is set up from.
+pub struct Settings {
+    pub mode: Mode,
+    pub first: Player,
+    pub length: MatchLength,
+    pub layout: Layout,
+    pub engine: EngineKind,
+    pub strength: Strength,
+    pub easing: Easing,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            mode: config::COMPUTER_PLAYER.map_or(Mode::HumanVsHuman, Mode::VsComputer),
+            first: config::FIRST_PLAYER,
+            length: config::MATCH_LENGTH,
+            layout: config::BOARD_LAYOUT,
+            engine: config::COMPUTER_ENGINE,
+            strength: config::COMPUTER_STRENGTH,
+            easing: config::ANIMATION_EASING,
+        }
+    }
+}
+
+impl Settings {
+    /// The first position of the match, which is `config::START_POSITION` on
+    /// the chosen board when set, with its own side to move.
+    pub fn game(&self) -> GameState {
+        let board = Board::with_layout(self.layout);
+        match config::START_POSITION {
+            Some(text) => position::read_position(text, board)
+                .unwrap_or_else(|error| panic!("Invalid start position {:?}: {}", text, error)),
+            None => GameState::new(board, self.first),
+        }
+    }
+
+    fn value(&self, option: MenuOption) -> String {
+        match option {
+            MenuOption::Mode => match self.mode {
+                Mode::HumanVsHuman => "Human vs human".to_string(),
+                Mode::VsComputer(player) => format!("Vs computer, computer is {:?}", player),
+                Mode::Network => "Network, not available yet".to_string(),
+            },
+            MenuOption::First => format!("{:?}", self.first),
+            MenuOption::Length => match self.length {
+                MatchLength::Custom(points) => format!("First to {}", points),
+                length => format!("{:?}, first to {}", length, length.points()),
+            },
+            MenuOption::Layout => format!("{:?}", self.layout),
+            MenuOption::Engine => format!("{:?}", self.engine),
+            MenuOption::Strength => format!("{:?}", self.strength),
+            MenuOption::Animation => format!("{:?}", self.easing),
+        }
+    }
+
+    fn change(&mut self, option: MenuOption, step: isize) {
+        match option {
+            MenuOption::Mode => self.mode = cycle(&Mode::ALL, self.mode, step),
+            MenuOption::First => {
+                self.first = cycle(&[Player::White, Player::Black], self.first, step)
+            }
+            MenuOption::Length => {
+                self.length = cycle(
+                    &[
+                        MatchLength::Single,
+                        MatchLength::Standard,
+                        MatchLength::Long,
+                        MatchLength::Marathon,
+                        MatchLength::Custom(30),
+                    ],
+                    self.length,
+                    step,
+                )
+            }
+            MenuOption::Layout => self.layout = cycle(&Layout::ALL, self.layout, step),
+            MenuOption::Engine => self.engine = cycle(&EngineKind::ALL, self.engine, step),
+            MenuOption::Strength => {
+                self.strength = cycle(
+                    &[Strength::Beginner, Strength::Intermediate, Strength::Expert],
+                    self.strength,
+                    step,
+                )
+            }
+            MenuOption::Animation => self.easing = cycle(&Easing::ALL, self.easing, step),
+        }
+    }
+}
+
+/// The option `step` places after `current`, wrapping around. A value that
+/// isn't on offer, like a custom match length other than 30, starts over from
+/// the first.
+fn cycle<T: Copy + PartialEq>(options: &[T], current: T, step: isize) -> T {
+    let index = match options.iter().position(|&option| option == current) {
+        Some(index) => index as isize + step,
+        None => 0,
+    };
+    options[index.rem_euclid(options.len() as isize) as usize]
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum MenuOption {
+    Mode,
+    First,
+    Length,
+    Layout,
+    Engine,
+    Strength,
+    Animation,
+}
+
+impl MenuOption {
+    const ALL: [MenuOption; 7] = [
+        MenuOption::Mode,
+        MenuOption::First,
+        MenuOption::Length,
+        MenuOption::Layout,
+        MenuOption::Engine,
+        MenuOption::Strength,
+        MenuOption::Animation,
+    ];
+
+    fn name(&self) -> &'static str {
+        match *self {
+            MenuOption::Mode => "Players",
+            MenuOption::First => "First move",
+            MenuOption::Length => "Match",
+            MenuOption::Layout => "Board",
+            MenuOption::Engine => "Computer",
+            MenuOption::Strength => "Strength",
+            MenuOption::Animation => "Animation",
+        }
+    }
+}
+
+/// The option the arrow keys change.
+#[derive(Default)]
+pub struct MenuCursor(usize);
+
+/// Everything on the menu or the game over screen, despawned on leaving them.
+#[derive(Component)]
+pub struct Overlay;
+
+#[derive(Component)]
+pub struct MenuText;
+
+/// A full window node centring its children, `color` showing behind them.
+fn overlay(color: Color) -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        color: UiColor(color),
+        ..Default::default()
+    }
+}
+
+pub fn setup_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    cursor: Res<MenuCursor>,
+    settings: Res<Settings>,
+) {
+    let font = asset_server.load(config::FONT);
+
+    commands
+        .spawn_bundle(overlay(Color::rgb(0.1, 0.1, 0.12)))
+        .insert(Overlay)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text {
+                        sections: menu_sections(&cursor, &settings, font),
+                        alignment: TextAlignment::default(),
+                    },
+                    ..Default::default()
+                })
+                .insert(MenuText);
+        });
+}
+
+/// Up and Down pick an option, Left and Right change it and Enter starts the
+/// game, unless network play is chosen. The keys are cleared on leaving, as the next screen runs in the same
+/// frame and would see them pressed too.
+pub fn menu_input(
+    mut keys: ResMut<Input<KeyCode>>,
+    mut cursor: ResMut<MenuCursor>,
+    mut settings: ResMut<Settings>,
+    mut screen: ResMut<AppState<Screen>>,
+) {
+    let count = MenuOption::ALL.len();
+
+    if keys.just_pressed(KeyCode::Up) {
+        cursor.0 = (cursor.0 + count - 1) % count;
+    } else if keys.just_pressed(KeyCode::Down) {
+        cursor.0 = (cursor.0 + 1) % count;
+    } else if keys.just_pressed(KeyCode::Left) {
+        settings.change(MenuOption::ALL[cursor.0], -1);
+    } else if keys.just_pressed(KeyCode::Right) {
+        settings.change(MenuOption::ALL[cursor.0], 1);
+    } else if (keys.just_pressed(KeyCode::Return) || keys.just_pressed(KeyCode::NumpadEnter))
+        && settings.mode != Mode::Network
+    {
+        screen.set(Screen::Playing).unwrap();
+        keys.clear();
+    }
+}
+
+pub fn update_menu(
+    cursor: Res<MenuCursor>,
+    settings: Res<Settings>,
+    mut texts: Query<&mut Text, With<MenuText>>,
+) {
+    if !cursor.is_changed() && !settings.is_changed() {
+        return;
+    }
+
+    for mut text in texts.iter_mut() {
+        let font = text.sections[0].style.font.clone();
+        text.sections = menu_sections(&cursor, &settings, font);
+    }
+}
+
+/// Lists the options with the chosen one highlighted. The computer's options
+/// are dimmed when nobody plays it, and so is network play, which can't start.
+fn menu_sections(cursor: &MenuCursor, settings: &Settings, font: Handle<Font>) -> Vec<TextSection> {
+    let gold = Color::rgb(1.0, 0.8, 0.1);
+    let section = |value: String, color: Color| TextSection {
+        value,
+        style: TextStyle {
+            font: font.clone(),
+            font_size: 22.0,
+            color,
+        },
+    };
+
+    let mut sections = vec![section("KAMISADO\n\n".to_string(), gold)];
+
+    for (index, &option) in MenuOption::ALL.iter().enumerate() {
+        let selected = index == cursor.0;
+        let dimmed = match option {
+            MenuOption::Mode => settings.mode == Mode::Network,
+            MenuOption::Engine | MenuOption::Strength => {
+                !matches!(settings.mode, Mode::VsComputer(_))
+            }
+            _ => false,
+        };
+
+        let line = format!(
+            "{} {:<11} {}\n",
+            if selected { ">" } else { " " },
+            option.name(),
+            settings.value(option)
+        );
+        let color = if selected {
+            gold
+        } else if dimmed {
+            Color::GRAY
+        } else {
+            Color::WHITE
+        };
+        sections.push(section(line, color));
+    }
+
+    sections.push(section(
+        "\nUp/Down choose, Left/Right change, Enter starts".to_string(),
+        Color::GRAY,
+    ));
+    sections
+}
+
+/// The result of the match over the final position, with the way back to the
+/// menu.
+pub fn setup_game_over(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    match_play: Res<Match>,
+) {
+    let winner = match match_play.winner() {
+        Some(winner) => winner,
+        None => return,
+    };
+    let text = format!(
+        "{:?} wins the match\nWhite {} - Black {}\n\nEnter: back to the menu\nN: play again",
+        winner,
+        match_play.score(Player::White),
+        match_play.score(Player::Black)
+    );
+
+    commands
+        .spawn_bundle(overlay(Color::NONE))
+        .insert(Overlay)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        padding: Rect::all(Val::Px(20.0)),
+                        ..Default::default()
+                    },
+                    color: UiColor(Color::rgba(0.1, 0.1, 0.12, 0.9)),
+                    ..Default::default()
+                })
+                .with_children(|panel| {
+                    panel.spawn_bundle(TextBundle {
+                        text: Text::with_section(
+                            text,
+                            TextStyle {
+                                font: asset_server.load(config::FONT),
+                                font_size: 22.0,
+                                color: Color::WHITE,
+                            },
+                            TextAlignment::default(),
+                        ),
+                        ..Default::default()
+                    });
+                });
+        });
+}
+
+/// Leaves the finished match for the menu, or for a new one with the same
+/// settings.
+pub fn game_over_input(mut keys: ResMut<Input<KeyCode>>, mut screen: ResMut<AppState<Screen>>) {
+    let next = if keys.just_pressed(KeyCode::Return) || keys.just_pressed(KeyCode::NumpadEnter) {
+        Screen::Menu
+    } else if keys.just_pressed(KeyCode::N) {
+        Screen::NewGame
+    } else {
+        return;
+    };
+
+    screen.replace(next).unwrap();
+    keys.clear();
+}
+
+pub fn close_overlay(mut commands: Commands, overlays: Query<Entity, With<Overlay>>) {
+    overlays.for_each(|entity| commands.entity(entity).despawn_recursive());
+}
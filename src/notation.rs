@@ -0,0 +1,93 @@
+use crate::game::{GameState, Move, MoveKind, Player};
+
+/// A square as a file letter and rank number, `a1` being White's left corner.
+pub fn square((x, y): (usize, usize)) -> String {
+    format!("{}{}", (b'a' + x as u8) as char, y + 1)
+}
+
+/// A move as the side and tower colour followed by its squares, such as
+/// `W-Orange a1-a5`. A pass only names the blocked tower's square and a sumo
+/// push is marked after the squares.
+pub fn write_move(game: &GameState, mv: Move) -> String {
+    let (player, color) = game.tower_at(mv.from).unwrap();
+    let side = match player {
+        Player::White => 'W',
+        Player::Black => 'B',
+    };
+    let tower = format!("{}-{:?}", side, color);
+
+    match mv.kind {
+        MoveKind::Normal => format!("{} {}-{}", tower, square(mv.from), square(mv.to)),
+        MoveKind::Pass => format!("{} {} pass", tower, square(mv.from)),
+        MoveKind::SumoPush => format!("{} {}-{} push", tower, square(mv.from), square(mv.to)),
+    }
+}
+
+/// The legal move in `game` written as `text`, if there is one.
+pub fn read_move(game: &GameState, text: &str) -> Option<Move> {
+    let text = text.trim();
+    game.legal_moves()
+        .into_iter()
+        .find(|&mv| write_move(game, mv) == text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{board::Board, position::read_position};
+
+    /// Writes the only move of `kind` in `text` and reads it back.
+    fn round_trip(text: &str, kind: MoveKind) -> String {
+        let game = read_position(text, Board::new()).unwrap();
+        let mv = game
+            .legal_moves()
+            .into_iter()
+            .find(|mv| mv.kind == kind)
+            .unwrap();
+
+        let written = write_move(&game, mv);
+        assert_eq!(read_move(&game, &written), Some(mv));
+        written
+    }
+
+    #[test]
+    fn normal_move() {
+        let game = GameState::new(Board::new(), Player::White);
+        let mv = read_move(&game, "W-Orange a1-a5").unwrap();
+        assert_eq!(
+            (mv.from, mv.to, mv.kind),
+            ((0, 0), (0, 4), MoveKind::Normal)
+        );
+        assert_eq!(write_move(&game, mv), "W-Orange a1-a5");
+    }
+
+    /// White's orange tower on b7 faces three of Black's towers.
+    #[test]
+    fn pass() {
+        let text = "ngrykpbo/1O6/8/8/8/8/8/1BPKYRGN w o";
+        assert_eq!(round_trip(text, MoveKind::Pass), "W-Orange b7 pass");
+    }
+
+    #[test]
+    fn sumo_push() {
+        let text = "1grykpbo/8/8/8/n7/O+7/8/1BPKYRGN w o";
+        assert_eq!(round_trip(text, MoveKind::SumoPush), "W-Orange a3-a4 push");
+    }
+
+    #[test]
+    fn rejects_malformed_and_illegal_moves() {
+        let game = GameState::new(Board::new(), Player::White);
+        for text in [
+            "",
+            "W-Orange",
+            "W-Orange a1 a5",
+            "w-orange a1-a5",
+            "W-Orange a1-a9",
+            "W-Purple a1-a5",
+            "B-Orange h8-h4",
+            "W-Orange a1-a8",
+        ] {
+            assert_eq!(read_move(&game, text), None, "{:?}", text);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::time::Instant;
+
+use crate::{board::Board, config, game::GameState};
+
+/// Counts the move sequences `depth` plies long from `game`, stopping early
+/// where a game ends. Passes and sumo pushes are plies like any other move.
+pub fn perft(game: &GameState, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let moves = game.legal_moves();
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    moves
+        .into_iter()
+        .map(|mv| {
+            let mut next = game.clone();
+            next.apply(mv);
+            perft(&next, depth - 1)
+        })
+        .sum()
+}
+
+/// Prints the node counts from the opening up to `depth`, for checking the
+/// move generator from the command line.
+pub fn print(depth: u32) {
+    let game = GameState::new(Board::new(), config::FIRST_PLAYER);
+
+    for depth in 1..=depth {
+        let start = Instant::now();
+        let nodes = perft(&game, depth);
+        println!("perft({}) = {} in {:?}", depth, nodes, start.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bitboard::Bitboard,
+        game::{MoveKind, Player, Rank},
+    };
+
+    /// The moves at the last of `depth` plies, split into normal moves, passes
+    /// and sumo pushes.
+    fn kinds(game: &GameState, depth: u32) -> [u64; 3] {
+        let mut counts = [0; 3];
+
+        for mv in game.legal_moves() {
+            if depth == 1 {
+                counts[match mv.kind {
+                    MoveKind::Normal => 0,
+                    MoveKind::Pass => 1,
+                    MoveKind::SumoPush => 2,
+                }] += 1;
+            } else {
+                let mut next = game.clone();
+                next.apply(mv);
+                let below = kinds(&next, depth - 1);
+                for (count, below) in counts.iter_mut().zip(below) {
+                    *count += below;
+                }
+            }
+        }
+
+        counts
+    }
+
+    fn bitboard_perft(position: &mut Bitboard, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let mut moves = Vec::new();
+        position.legal_moves(&mut moves);
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+
+        moves
+            .into_iter()
+            .map(|mv| {
+                let undo = position.make(mv);
+                let nodes = bitboard_perft(position, depth - 1);
+                position.unmake(undo);
+                nodes
+            })
+            .sum()
+    }
+
+    /// The standard opening, with White's towers ranked `white` and Black's
+    /// ranked `black`.
+    fn ranked(white: Rank, black: Rank) -> GameState {
+        GameState::ranked_opening([[white; 8], [black; 8]], Player::White)
+    }
+
+    /// Depth 1 is worked out in `opening_moves_per_tower`. At depth 2 Black
+    /// moves the home-row tower of the colour White landed on, which has 12 or
+    /// 13 moves on an open board: 1,296 over White's 102 moves. White's tower
+    /// stands in the way of 146 of them, leaving 1,150. Depths 3 and 4 agree
+    /// with a separate perft written from the rules.
+    #[test]
+    fn opening() {
+        let game = GameState::new(Board::new(), Player::White);
+        let nodes: Vec<_> = (1..=4).map(|depth| perft(&game, depth)).collect();
+        assert_eq!(nodes, [102, 1_150, 11_182, 105_024]);
+    }
+
+    /// Each home-row tower has six squares straight ahead and as many on each
+    /// diagonal as fit before the board edge or Black's home row.
+    #[test]
+    fn opening_moves_per_tower() {
+        let game = GameState::new(Board::new(), Player::White);
+        let moves = game.legal_moves();
+
+        for x in 0..8 {
+            let from_tower: Vec<_> = moves.iter().filter(|mv| mv.from == (x, 0)).collect();
+            assert_eq!(from_tower.len(), 6 + x.min(6) + (7 - x).min(6));
+            assert!(from_tower.iter().all(|mv| mv.to.0 < 8 && mv.to.1 < 7));
+        }
+    }
+
+    /// The first passes come at depth 3, once White has moved a tower up to
+    /// the seventh rank and Black answers on that tower's colour without
+    /// freeing a square in front of it, as in a1-a7 f8-f6. Six of White's
+    /// moves allow 20 such answers. Deeper counts agree with a separate perft
+    /// written from the rules.
+    #[test]
+    fn passes() {
+        let game = GameState::new(Board::new(), Player::White);
+        assert_eq!(kinds(&game, 2), [1_150, 0, 0]);
+        assert_eq!(kinds(&game, 3), [11_162, 20, 0]);
+        assert_eq!(kinds(&game, 4), [104_900, 124, 0]);
+    }
+
+    /// Sumos move at most five squares, so the opening has 90 moves instead of
+    /// 102, and the first push comes at depth 5, on White's third move. The
+    /// counts agree with a separate perft written from the rules.
+    #[test]
+    fn sumo_pushes() {
+        let game = ranked(Rank::Sumo, Rank::Normal);
+        assert_eq!(kinds(&game, 1), [90, 0, 0]);
+        assert_eq!(kinds(&game, 4), [87_188, 106, 0]);
+        assert_eq!(kinds(&game, 5), [700_646, 182, 862]);
+
+        let game = ranked(Rank::DoubleSumo, Rank::Sumo);
+        assert_eq!(kinds(&game, 5), [230_302, 42, 4]);
+    }
+
+    #[test]
+    fn bitboard_agrees() {
+        for (game, depth) in [
+            (GameState::new(Board::new(), Player::Black), 4),
+            (ranked(Rank::Sumo, Rank::Normal), 5),
+            (ranked(Rank::DoubleSumo, Rank::Sumo), 5),
+        ] {
+            let mut position = Bitboard::from_game(&game);
+            assert_eq!(bitboard_perft(&mut position, depth), perft(&game, depth));
+        }
+    }
+}
@@ -1,21 +1,31 @@
 use bevy::{
     math::Vec3,
-    prelude::{BuildChildren, Bundle, Color, Commands, Component, GlobalTransform, Transform},
+    prelude::{
+        BuildChildren, Bundle, Changed, ChildBuilder, Children, Color, Commands, Component,
+        GlobalTransform, Query, Res, Transform, Visibility,
+    },
     sprite::{Sprite, SpriteBundle},
 };
 
-use crate::{colors::Colors, config};
+use crate::{
+    animation::Tween,
+    colors::Colors,
+    config,
+    game::{GameState, Player, Rank},
+    InGame,
+};
 
 #[derive(Component)]
 pub struct Piece {
     color: Colors,
     x: usize,
     y: usize,
+    rank: Rank,
 }
 
 impl Piece {
-    fn new(color: Colors, x: usize, y: usize) -> Self {
-        Self { color, x, y }
+    fn new(color: Colors, x: usize, y: usize, rank: Rank) -> Self {
+        Self { color, x, y, rank }
     }
 
     pub fn color(&self) -> Colors {
@@ -30,10 +40,18 @@ impl Piece {
         self.y
     }
 
+    pub fn rank(&self) -> Rank {
+        self.rank
+    }
+
     pub fn move_piece(&mut self, x: usize, y: usize) {
         self.x = x;
         self.y = y;
     }
+
+    pub fn set_rank(&mut self, rank: Rank) {
+        self.rank = rank;
+    }
 }
 
 #[derive(Component)]
@@ -42,33 +60,31 @@ pub struct WhitePiece;
 #[derive(Component)]
 pub struct BlackPiece;
 
+#[derive(Component)]
+pub struct SumoTooth(usize);
+
+/// Pieces stand above the move and forced tower markers, below the pass cover.
+const PIECE_Z: f32 = 0.5;
+
 #[derive(Bundle)]
 pub struct PieceBundle {
     transform: Transform,
     global_transform: GlobalTransform,
 }
 
-pub fn create_pieces(mut commands: Commands) {
+pub fn create_pieces(mut commands: Commands, game: Res<GameState>) {
     let tile_size = config::BOARD_SIZE / 8.0;
     let piece_size = tile_size / 1.5;
 
-    for (i, &color) in [
-        Colors::Orange,
-        Colors::Blue,
-        Colors::Purple,
-        Colors::Pink,
-        Colors::Yellow,
-        Colors::Red,
-        Colors::Green,
-        Colors::Brown,
-    ]
-    .iter()
-    .enumerate()
-    {
+    for color in Colors::ALL {
+        let (white_x, white_y) = game.tower(Player::White, color);
+        let (black_x, black_y) = game.tower(Player::Black, color);
+
         //White
         commands
             .spawn_bundle(PieceBundle {
                 transform: Transform {
+                    translation: Vec3::new(0.0, 0.0, PIECE_Z),
                     scale: Vec3::new(piece_size, piece_size, 0.0),
                     ..Default::default()
                 },
@@ -99,14 +115,23 @@ pub fn create_pieces(mut commands: Commands) {
                     },
                     ..Default::default()
                 });
+                spawn_teeth(parent, Color::rgb(0.0, 0.0, 0.0));
             })
-            .insert(Piece::new(color, i, 0))
-            .insert(WhitePiece);
+            .insert(Piece::new(
+                color,
+                white_x,
+                white_y,
+                game.rank(Player::White, color),
+            ))
+            .insert(WhitePiece)
+            .insert(Tween::default())
+            .insert(InGame);
 
         //Black
         commands
             .spawn_bundle(PieceBundle {
                 transform: Transform {
+                    translation: Vec3::new(0.0, 0.0, PIECE_Z),
                     scale: Vec3::new(piece_size, piece_size, 0.0),
                     ..Default::default()
                 },
@@ -137,8 +162,50 @@ pub fn create_pieces(mut commands: Commands) {
                     },
                     ..Default::default()
                 });
+                spawn_teeth(parent, Color::rgb(1.0, 1.0, 1.0));
             })
-            .insert(Piece::new(color, 7 - i, 7))
-            .insert(BlackPiece);
+            .insert(Piece::new(
+                color,
+                black_x,
+                black_y,
+                game.rank(Player::Black, color),
+            ))
+            .insert(BlackPiece)
+            .insert(Tween::default())
+            .insert(InGame);
+    }
+}
+
+/// Every tower carries four hidden teeth, one shown per sumo rank.
+fn spawn_teeth(parent: &mut ChildBuilder, color: Color) {
+    for i in 0..4 {
+        parent
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    ..Default::default()
+                },
+                transform: Transform {
+                    translation: Vec3::new(-0.3 + 0.2 * i as f32, 0.375, 0.1),
+                    scale: Vec3::new(0.12, 0.12, 0.0),
+                    ..Default::default()
+                },
+                visibility: Visibility { is_visible: false },
+                ..Default::default()
+            })
+            .insert(SumoTooth(i));
+    }
+}
+
+pub fn show_sumo_teeth(
+    pieces: Query<(&Piece, &Children), Changed<Piece>>,
+    mut teeth: Query<(&SumoTooth, &mut Visibility)>,
+) {
+    for (piece, children) in pieces.iter() {
+        for &child in children.iter() {
+            if let Ok((tooth, mut visibility)) = teeth.get_mut(child) {
+                visibility.is_visible = tooth.0 < piece.rank().teeth();
+            }
+        }
     }
 }
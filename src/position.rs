@@ -0,0 +1,290 @@
+use std::fmt;
+
+use crate::{
+    board::Board,
+    colors::Colors,
+    game::{GameState, Player, Rank},
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PositionError {
+    /// The towers, side to move and forced colour are separated by spaces.
+    Fields(usize),
+    Rows(usize),
+    /// A row that doesn't cover exactly eight squares, counted from the top.
+    RowLength(usize),
+    Character(char),
+    Duplicate(Player, Colors),
+    Missing(Player, Colors),
+    Side(String),
+    Forced(String),
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PositionError::Fields(count) => write!(f, "expected 3 fields, found {}", count),
+            PositionError::Rows(count) => write!(f, "expected 8 rows, found {}", count),
+            PositionError::RowLength(row) => write!(f, "row {} is not 8 squares long", row + 1),
+            PositionError::Character(c) => write!(f, "unexpected character {:?}", c),
+            PositionError::Duplicate(player, color) => {
+                write!(f, "more than one {:?} {:?} tower", player, color)
+            }
+            PositionError::Missing(player, color) => {
+                write!(f, "no {:?} {:?} tower", player, color)
+            }
+            PositionError::Side(side) => write!(f, "side to move {:?} is not w or b", side),
+            PositionError::Forced(forced) => {
+                write!(f, "forced colour {:?} is not a colour letter or -", forced)
+            }
+        }
+    }
+}
+
+/// Writes a position on one line, FEN style: the rows from Black's home row
+/// down to White's, separated by `/`, then the side to move and the forced
+/// colour. A tower is its colour's letter, upper case for White and lower case
+/// for Black, followed by a `+` for each sumo rank. Digits count empty squares
+/// and `-` stands for no forced colour. The opening is
+///
+/// ```text
+/// ngrykpbo/8/8/8/8/8/8/OBPKYRGN w -
+/// ```
+pub fn write_position(game: &GameState) -> String {
+    let mut rows = Vec::new();
+
+    for y in (0..8).rev() {
+        let mut row = String::new();
+        let mut empty = 0;
+
+        for x in 0..8 {
+            match game.tower_at((x, y)) {
+                Some((player, color)) => {
+                    if empty > 0 {
+                        row += &empty.to_string();
+                        empty = 0;
+                    }
+                    row.push(match player {
+                        Player::White => color.letter().to_ascii_uppercase(),
+                        Player::Black => color.letter(),
+                    });
+                    row += &"+".repeat(game.rank(player, color) as usize);
+                }
+                None => empty += 1,
+            }
+        }
+
+        if empty > 0 {
+            row += &empty.to_string();
+        }
+        rows.push(row);
+    }
+
+    let side = match game.to_move() {
+        Player::White => 'w',
+        Player::Black => 'b',
+    };
+    let forced = game.forced().map_or('-', |color| color.letter());
+
+    format!("{} {} {}", rows.join("/"), side, forced)
+}
+
+/// Reads a position written by `write_position`, placing it on `board`.
+pub fn read_position(text: &str, board: Board) -> Result<GameState, PositionError> {
+    let fields: Vec<_> = text.split_whitespace().collect();
+    let (rows, side, forced) = match fields[..] {
+        [rows, side, forced] => (rows, side, forced),
+        _ => return Err(PositionError::Fields(fields.len())),
+    };
+
+    let rows: Vec<_> = rows.split('/').collect();
+    if rows.len() != 8 {
+        return Err(PositionError::Rows(rows.len()));
+    }
+
+    let mut towers = [[None; 8]; 2];
+    let mut ranks = [[Rank::Normal; 8]; 2];
+
+    for (row, text) in rows.into_iter().enumerate() {
+        let y = 7 - row;
+        let mut x = 0;
+        let mut last: Option<(Player, Colors)> = None;
+
+        for c in text.chars() {
+            if let Some(empty) = c.to_digit(10) {
+                x += empty as usize;
+                last = None;
+            } else if c == '+' {
+                let (player, color) = last.ok_or(PositionError::Character(c))?;
+                let rank = &mut ranks[player as usize][color.index()];
+                *rank = *Rank::ALL
+                    .get(*rank as usize + 1)
+                    .ok_or(PositionError::Character(c))?;
+            } else {
+                let color = Colors::from_letter(c.to_ascii_lowercase())
+                    .ok_or(PositionError::Character(c))?;
+                let player = if c.is_ascii_uppercase() {
+                    Player::White
+                } else {
+                    Player::Black
+                };
+
+                if x >= 8 {
+                    return Err(PositionError::RowLength(row));
+                }
+                let tower = &mut towers[player as usize][color.index()];
+                if tower.is_some() {
+                    return Err(PositionError::Duplicate(player, color));
+                }
+                *tower = Some((x, y));
+                x += 1;
+                last = Some((player, color));
+            }
+        }
+
+        if x != 8 {
+            return Err(PositionError::RowLength(row));
+        }
+    }
+
+    let mut squares = [[(0, 0); 8]; 2];
+    for player in [Player::White, Player::Black] {
+        for color in Colors::ALL {
+            squares[player as usize][color.index()] = towers[player as usize][color.index()]
+                .ok_or(PositionError::Missing(player, color))?;
+        }
+    }
+
+    let to_move = match side {
+        "w" => Player::White,
+        "b" => Player::Black,
+        _ => return Err(PositionError::Side(side.to_string())),
+    };
+
+    let forced = match forced {
+        "-" => None,
+        _ => {
+            let mut chars = forced.chars();
+            match (chars.next().and_then(Colors::from_letter), chars.next()) {
+                (Some(color), None) => Some(color),
+                _ => return Err(PositionError::Forced(forced.to_string())),
+            }
+        }
+    };
+
+    let mut game = GameState::from_towers(board, squares, ranks, to_move);
+    game.set_forced(forced);
+    Ok(game)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        game::{MoveKind, WinReason},
+        test_support::Rng,
+    };
+
+    #[test]
+    fn opening() {
+        let game = GameState::new(Board::new(), Player::White);
+        assert_eq!(write_position(&game), "ngrykpbo/8/8/8/8/8/8/OBPKYRGN w -");
+
+        let read = read_position("ngrykpbo/8/8/8/8/8/8/OBPKYRGN b -", Board::new()).unwrap();
+        assert_eq!(read.to_move(), Player::Black);
+        assert_eq!(
+            read.legal_moves(),
+            GameState::new(Board::new(), Player::Black).legal_moves()
+        );
+    }
+
+    /// Writes and reads back the positions of pseudo-random games with sumos.
+    #[test]
+    fn round_trip() {
+        let mut rng = Rng::new(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..50 {
+            let mut ranks = [[Rank::Normal; 8]; 2];
+            for rank in ranks.iter_mut().flatten() {
+                *rank = Rank::ALL[rng.below(5)];
+            }
+            let mut game = GameState::ranked_opening(ranks, Player::White);
+
+            while !game.is_terminal() {
+                let text = write_position(&game);
+                let read = read_position(&text, Board::new()).unwrap();
+
+                assert_eq!(write_position(&read), text);
+                assert_eq!(read.legal_moves(), game.legal_moves());
+
+                let moves = game.legal_moves();
+                game.apply(moves[rng.below(moves.len())]);
+            }
+        }
+    }
+
+    /// Both blocked towers pass in turn, and the deadlock goes against Black,
+    /// whose move forced White's orange tower.
+    #[test]
+    fn forced_position_deadlocks() {
+        let mut game =
+            read_position("n4pb1/8/rB2Y3/O3yk1N/3ogP2/8/2K5/5RG1 w o", Board::new()).unwrap();
+        assert_eq!(game.last_mover(), Some(Player::Black));
+
+        for _ in 0..4 {
+            if game.is_terminal() {
+                break;
+            }
+            let moves = game.legal_moves();
+            assert!(moves.iter().all(|mv| mv.kind == MoveKind::Pass));
+            game.apply(moves[0]);
+        }
+
+        assert!(game.is_terminal());
+        assert_eq!(game.result(), Some((Player::White, WinReason::Deadlock)));
+    }
+
+    #[test]
+    fn errors() {
+        let opening = "ngrykpbo/8/8/8/8/8/8/OBPKYRGN";
+        let read = |text: &str| read_position(text, Board::new()).err();
+
+        assert_eq!(read(opening), Some(PositionError::Fields(1)));
+        assert_eq!(
+            read("ngrykpbo/8/8/8/8/8/OBPKYRGN w -"),
+            Some(PositionError::Rows(7))
+        );
+        assert_eq!(
+            read("ngrykpbo/8/8/8/8/8/7/OBPKYRGN w -"),
+            Some(PositionError::RowLength(6))
+        );
+        assert_eq!(
+            read("ngrykpbo/8/8/8/8/8/8/OBPKYRGN1 w -"),
+            Some(PositionError::RowLength(7))
+        );
+        assert_eq!(
+            read("ngrykpbx/8/8/8/8/8/8/OBPKYRGN w -"),
+            Some(PositionError::Character('x'))
+        );
+        assert_eq!(
+            read("ngrykpb+++++o/8/8/8/8/8/8/OBPKYRGN w -"),
+            Some(PositionError::Character('+'))
+        );
+        assert_eq!(
+            read("ngrykpbo/8/8/8/8/8/8/OBPKYRGO w -"),
+            Some(PositionError::Duplicate(Player::White, Colors::Orange))
+        );
+        assert_eq!(
+            read("ngrykpbo/8/8/8/8/8/8/OBPKYRG1 w -"),
+            Some(PositionError::Missing(Player::White, Colors::Brown))
+        );
+        assert_eq!(
+            read(&format!("{} x -", opening)),
+            Some(PositionError::Side("x".to_string()))
+        );
+        assert_eq!(
+            read(&format!("{} w oo", opening)),
+            Some(PositionError::Forced("oo".to_string()))
+        );
+    }
+}
@@ -0,0 +1,434 @@
+use std::{
+    fmt, fs, io,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    board::{Board, Layout},
+    game::{GameState, Player},
+    history::History,
+    match_play::{Fill, Match, MatchLength},
+    notation, position,
+};
+
+/// Who played a recorded match, when, and under which settings.
+pub struct Header {
+    pub white: String,
+    pub black: String,
+    pub date: String,
+    pub length: MatchLength,
+    pub first: Player,
+    pub layout: Layout,
+}
+
+/// A match read back from a record, replayed up to its last move.
+pub struct Record {
+    pub header: Header,
+    pub match_play: Match,
+    pub game: GameState,
+    pub history: History,
+}
+
+#[derive(Debug)]
+pub enum RecordError {
+    Io(io::Error),
+    /// A header tag is missing or its value can't be read.
+    Header(&'static str),
+    /// A move that isn't legal at its place in the round.
+    Move {
+        round: usize,
+        text: String,
+    },
+    /// A new round starts before the previous one was won.
+    Unfinished(usize),
+    Line(String),
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecordError::Io(error) => write!(f, "{}", error),
+            RecordError::Header(tag) => write!(f, "missing or invalid {} header", tag),
+            RecordError::Move { round, text } => {
+                write!(f, "illegal move in round {}: {}", round, text)
+            }
+            RecordError::Unfinished(round) => write!(f, "round {} was not finished", round),
+            RecordError::Line(line) => write!(f, "unreadable line: {}", line),
+        }
+    }
+}
+
+impl From<io::Error> for RecordError {
+    fn from(error: io::Error) -> Self {
+        RecordError::Io(error)
+    }
+}
+
+/// Writes the header as `[Tag "value"]` lines, then every round with its fill
+/// and numbered moves:
+///
+/// ```text
+/// [White "Human"]
+/// [Black "Human"]
+/// [Date "2024-05-01"]
+/// [Match "Standard"]
+/// [First "White"]
+///
+/// Round 1
+/// 1. W-Orange a1-a5
+/// 2. B-Yellow e8-e6
+///
+/// Round 2 Left
+/// ```
+///
+/// Quotes and backslashes in a value are escaped with a backslash. A match on
+/// the mirrored board adds a `Layout` tag and one that didn't start from the
+/// opening a `Position` tag with the starting position string. Moves undone in
+/// the current round are left out.
+pub fn write(header: &Header, history: &History) -> String {
+    let mut text = String::new();
+
+    for (tag, value) in [
+        ("White", header.white.clone()),
+        ("Black", header.black.clone()),
+        ("Date", header.date.clone()),
+        ("Match", write_length(header.length)),
+        ("First", format!("{:?}", header.first)),
+    ] {
+        text += &format!("[{} \"{}\"]\n", tag, escape(&value));
+    }
+
+    if header.layout != Layout::Standard {
+        text += &format!("[Layout \"{:?}\"]\n", header.layout);
+    }
+
+    let board = Board::with_layout(header.layout);
+    let start = position::write_position(&history.rounds()[0].start);
+    if start != position::write_position(&GameState::new(board, header.first)) {
+        text += &format!("[Position \"{}\"]\n", start);
+    }
+
+    let rounds = history.rounds();
+    for (index, round) in rounds.iter().enumerate() {
+        text += &format!("\nRound {}", index + 1);
+        if let Some(fill) = round.fill {
+            text += &format!(" {:?}", fill);
+        }
+        text += "\n";
+
+        let moves = if index + 1 == rounds.len() {
+            &round.moves[..history.ply()]
+        } else {
+            &round.moves[..]
+        };
+
+        let mut game = round.start.clone();
+        for (ply, &mv) in moves.iter().enumerate() {
+            text += &format!("{}. {}\n", ply + 1, notation::write_move(&game, mv));
+            game.apply(mv);
+        }
+    }
+
+    text
+}
+
+/// Reads a record written by `write`, replaying every move to check it and
+/// scoring each finished round except the last, which is left for the caller
+/// to score as if it had just been played.
+pub fn read(text: &str) -> Result<Record, RecordError> {
+    let mut tags = Vec::new();
+    let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+    let mut line = lines.next();
+
+    while let Some(tag) = line.and_then(|line| line.strip_prefix('[')) {
+        let (name, value) = tag
+            .strip_suffix(']')
+            .and_then(|tag| tag.split_once(' '))
+            .and_then(|(name, value)| Some((name, unescape(value)?)))
+            .ok_or_else(|| RecordError::Line(tag.to_string()))?;
+        tags.push((name, value));
+        line = lines.next();
+    }
+
+    let tag = |name: &'static str| {
+        tags.iter()
+            .find(|&&(tag, _)| tag == name)
+            .map(|(_, value)| value.clone())
+            .ok_or(RecordError::Header(name))
+    };
+
+    let header = Header {
+        white: tag("White")?,
+        black: tag("Black")?,
+        date: tag("Date")?,
+        length: read_length(&tag("Match")?).ok_or(RecordError::Header("Match"))?,
+        first: match tag("First")?.as_str() {
+            "White" => Player::White,
+            "Black" => Player::Black,
+            _ => return Err(RecordError::Header("First")),
+        },
+        layout: match tag("Layout").as_deref() {
+            Ok("Mirrored") => Layout::Mirrored,
+            Ok("Standard") | Err(_) => Layout::Standard,
+            Ok(_) => return Err(RecordError::Header("Layout")),
+        },
+    };
+
+    let mut match_play = Match::new(header.length);
+    let board = Board::with_layout(header.layout);
+    let mut game = match tag("Position") {
+        Ok(start) => {
+            position::read_position(&start, board).map_err(|_| RecordError::Header("Position"))?
+        }
+        Err(_) => GameState::new(board, header.first),
+    };
+    let mut history = History::new(game.clone());
+    let mut round = 0;
+
+    while let Some(entry) = line {
+        if let Some(rest) = entry.strip_prefix("Round ") {
+            let mut words = rest.split_whitespace();
+            let number = words.next().and_then(|number| number.parse::<usize>().ok());
+            let fill = match words.next() {
+                Some("Left") => Some(Fill::Left),
+                Some("Right") => Some(Fill::Right),
+                None => None,
+                _ => return Err(RecordError::Line(entry.to_string())),
+            };
+
+            // Rounds are numbered from 1 and every one after the first says
+            // how its towers were lined up.
+            if number != Some(round + 1) || fill.is_some() != (round > 0) || words.next().is_some()
+            {
+                return Err(RecordError::Line(entry.to_string()));
+            }
+
+            if let Some(fill) = fill {
+                if game.winner().is_none() {
+                    return Err(RecordError::Unfinished(round));
+                }
+                match_play.score_round(&game);
+                game = match_play.next_round(&game, fill);
+                history.next_round(game.clone(), fill);
+            }
+            round += 1;
+        } else if round == 0 {
+            return Err(RecordError::Line(entry.to_string()));
+        } else {
+            let written = entry.split_once(". ").map_or(entry, |(_, written)| written);
+            let mv = notation::read_move(&game, written).ok_or_else(|| RecordError::Move {
+                round,
+                text: entry.to_string(),
+            })?;
+            game.apply(mv);
+            history.push(mv);
+        }
+
+        line = lines.next();
+    }
+
+    Ok(Record {
+        header,
+        match_play,
+        game,
+        history,
+    })
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// The value of a quoted, escaped header value, `None` when a quote is
+/// unescaped or missing.
+fn unescape(quoted: &str) -> Option<String> {
+    let mut chars = quoted.strip_prefix('"')?.strip_suffix('"')?.chars();
+    let mut value = String::new();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => value.push(chars.next()?),
+            '"' => return None,
+            c => value.push(c),
+        }
+    }
+
+    Some(value)
+}
+
+fn write_length(length: MatchLength) -> String {
+    match length {
+        MatchLength::Custom(points) => format!("Custom {}", points),
+        length => format!("{:?}", length),
+    }
+}
+
+fn read_length(text: &str) -> Option<MatchLength> {
+    match text {
+        "Single" => Some(MatchLength::Single),
+        "Standard" => Some(MatchLength::Standard),
+        "Long" => Some(MatchLength::Long),
+        "Marathon" => Some(MatchLength::Marathon),
+        _ => text
+            .strip_prefix("Custom ")
+            .and_then(|points| points.parse().ok())
+            .map(MatchLength::Custom),
+    }
+}
+
+/// Today's date as `YYYY-MM-DD` in UTC.
+pub fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs() / 86_400) as i64;
+
+    // Civil calendar from days since 1970-01-01, after Howard Hinnant.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+pub fn save(path: &str, header: &Header, history: &History) -> Result<(), RecordError> {
+    Ok(fs::write(path, write(header, history))?)
+}
+
+pub fn load(path: &str) -> Result<Record, RecordError> {
+    read(&fs::read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Rng;
+
+    /// Plays a pseudo-random match over several rounds on the mirrored board and
+    /// reads it back.
+    #[test]
+    fn round_trip() {
+        let mut rng = Rng::new(0x9e37_79b9_7f4a_7c15);
+        let mut match_play = Match::new(MatchLength::Long);
+        let mut game = GameState::new(Board::with_layout(Layout::Mirrored), Player::Black);
+        let mut history = History::new(game.clone());
+
+        for round in 0..4 {
+            if round > 0 {
+                match_play.score_round(&game);
+                let fill = [Fill::Left, Fill::Right][round % 2];
+                game = match_play.next_round(&game, fill);
+                history.next_round(game.clone(), fill);
+            }
+
+            while !game.is_terminal() {
+                let moves = game.legal_moves();
+                let mv = moves[rng.below(moves.len())];
+                game.apply(mv);
+                history.push(mv);
+            }
+        }
+
+        // Leave the last round unfinished, with an undone move dropped.
+        history.undo();
+        history.undo();
+        let game = history.redo().unwrap();
+
+        let header = Header {
+            white: "Human".to_string(),
+            black: "Computer (Minimax, Expert)".to_string(),
+            date: "2024-05-01".to_string(),
+            length: MatchLength::Long,
+            first: Player::Black,
+            layout: Layout::Mirrored,
+        };
+        let text = write(&header, &history);
+        let record = read(&text).unwrap();
+
+        assert_eq!(record.header.black, header.black);
+        assert_eq!(record.header.first, Player::Black);
+        assert_eq!(record.header.layout, Layout::Mirrored);
+        assert_eq!(record.history.rounds().len(), 4);
+        assert_eq!(record.history.notation(), history.notation());
+        assert_eq!(record.match_play.round(), match_play.round());
+        for player in [Player::White, Player::Black] {
+            assert_eq!(record.match_play.score(player), match_play.score(player));
+            for color in crate::colors::Colors::ALL {
+                assert_eq!(record.game.tower(player, color), game.tower(player, color));
+                assert_eq!(record.game.rank(player, color), game.rank(player, color));
+            }
+        }
+        assert_eq!(write(&record.header, &record.history), text);
+    }
+
+    #[test]
+    fn rejects_illegal_moves() {
+        let text = "[White \"Human\"]\n[Black \"Human\"]\n[Date \"2024-05-01\"]\n\
+                    [Match \"Custom 5\"]\n[First \"White\"]\n\nRound 1\n1. W-Orange a1-a8\n";
+
+        assert!(matches!(
+            read(text),
+            Err(RecordError::Move { round: 1, .. })
+        ));
+        assert!(matches!(
+            read(&text.replace("Custom 5", "Custom")),
+            Err(RecordError::Header("Match"))
+        ));
+    }
+
+    #[test]
+    fn rejects_misplaced_rounds_and_moves() {
+        let header = "[White \"Human\"]\n[Black \"Human\"]\n[Date \"2024-05-01\"]\n\
+                      [Match \"Single\"]\n[First \"White\"]\n\n";
+
+        for (body, line) in [
+            ("1. W-Orange a1-a5\nRound 1\n", "1. W-Orange a1-a5"),
+            ("Round 2\n", "Round 2"),
+            ("Round 1 Left\n", "Round 1 Left"),
+            ("Round one\n", "Round one"),
+            ("Round 1\nRound 2\n", "Round 2"),
+            ("Round 1\nRound 3 Left\n", "Round 3 Left"),
+            ("Round 1 Left Right\n", "Round 1 Left Right"),
+        ] {
+            match read(&format!("{}{}", header, body)) {
+                Err(RecordError::Line(text)) => assert_eq!(text, line),
+                _ => panic!("{:?} was read", body),
+            }
+        }
+    }
+
+    #[test]
+    fn escapes_header_values() {
+        let history = History::new(GameState::new(Board::new(), Player::White));
+        let header = Header {
+            white: "Kim \"the Sumo\" [Club]".to_string(),
+            black: "C:\\Players\\".to_string(),
+            date: "2024-05-01".to_string(),
+            length: MatchLength::Single,
+            first: Player::White,
+            layout: Layout::Standard,
+        };
+
+        let text = write(&header, &history);
+        assert!(text.starts_with("[White \"Kim \\\"the Sumo\\\" [Club]\"]\n"));
+        let record = read(&text).unwrap();
+        assert_eq!(record.header.white, header.white);
+        assert_eq!(record.header.black, header.black);
+
+        let text = text.replace("\\\"the Sumo\\\"", "\"the Sumo\"");
+        assert!(matches!(read(&text), Err(RecordError::Line(_))));
+    }
+
+    #[test]
+    fn today_is_a_date() {
+        let date = today();
+        assert_eq!(date.len(), 10);
+        assert!(date.as_str() >= "2024-01-01");
+    }
+}
@@ -0,0 +1,232 @@
+use bevy::{
+    math::{Rect, Size},
+    prelude::{
+        AssetServer, BuildChildren, Color, Commands, Component, NodeBundle, Query, Res, TextBundle,
+        With, Without,
+    },
+    text::{Text, TextAlignment, TextStyle},
+    ui::{AlignItems, FlexDirection, PositionType, Style, UiColor, Val},
+};
+
+use crate::{
+    config,
+    game::{GameState, Player},
+    history::History,
+    match_play::Match,
+    InGame, State, Viewer,
+};
+
+#[derive(Component)]
+pub struct HistoryPanel;
+
+/// The lines of the HUD, top to bottom.
+#[derive(Component)]
+pub enum Hud {
+    Turn,
+    Forced,
+    Info,
+}
+
+/// The square next to the forced line showing the forced colour.
+#[derive(Component)]
+pub struct ForcedSwatch;
+
+pub fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let style = TextStyle {
+        font: asset_server.load(config::FONT),
+        font_size: 18.0,
+        color: Color::WHITE,
+    };
+    let text = |hud: Hud, font_size: f32| {
+        (
+            TextBundle {
+                text: Text::with_section(
+                    "",
+                    TextStyle {
+                        font_size,
+                        ..style.clone()
+                    },
+                    TextAlignment::default(),
+                ),
+                ..Default::default()
+            },
+            hud,
+        )
+    };
+
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(10.0),
+                    right: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text::with_section("", style.clone(), TextAlignment::default()),
+            ..Default::default()
+        })
+        .insert(HistoryPanel)
+        .insert(InGame);
+
+    // UI rows stack upwards, so the HUD column is reversed to read top down.
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(10.0),
+                    left: Val::Px(10.0),
+                    ..Default::default()
+                },
+                flex_direction: FlexDirection::ColumnReverse,
+                ..Default::default()
+            },
+            color: UiColor(Color::NONE),
+            ..Default::default()
+        })
+        .insert(InGame)
+        .with_children(|parent| {
+            let (bundle, hud) = text(Hud::Turn, 26.0);
+            parent.spawn_bundle(bundle).insert(hud);
+
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        align_items: AlignItems::Center,
+                        margin: Rect {
+                            top: Val::Px(6.0),
+                            bottom: Val::Px(6.0),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    color: UiColor(Color::NONE),
+                    ..Default::default()
+                })
+                .with_children(|row| {
+                    let (bundle, hud) = text(Hud::Forced, 18.0);
+                    row.spawn_bundle(bundle).insert(hud);
+                    row.spawn_bundle(NodeBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(20.0), Val::Px(20.0)),
+                            margin: Rect {
+                                left: Val::Px(8.0),
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        },
+                        color: UiColor(Color::NONE),
+                        ..Default::default()
+                    })
+                    .insert(ForcedSwatch);
+                });
+
+            let (bundle, hud) = text(Hud::Info, 18.0);
+            parent.spawn_bundle(bundle).insert(hud);
+        });
+}
+
+/// Lists the moves of the round, numbered from the first. When they don't fit,
+/// the first line counts the earlier moves left out.
+pub fn update_history_panel(
+    history: Res<History>,
+    mut panel: Query<&mut Text, With<HistoryPanel>>,
+) {
+    if !history.is_changed() {
+        return;
+    }
+
+    let moves = history.notation();
+    let skipped = match moves.len() {
+        count if count > config::HISTORY_LINES => count + 1 - config::HISTORY_LINES,
+        _ => 0,
+    };
+
+    // The count takes a line itself, so at least two moves give way to it.
+    let mut lines = Vec::new();
+    if skipped > 0 {
+        lines.push(format!("  ... {} earlier moves", skipped));
+    }
+    lines.extend(
+        moves
+            .iter()
+            .enumerate()
+            .skip(skipped)
+            .map(|(index, text)| format!("{:>3}. {}", index + 1, text)),
+    );
+
+    panel.single_mut().sections[0].value = lines.join("\n");
+}
+
+/// Shows whose turn it is and which colour they are forced to move, the move
+/// number, the match score and, once decided, the round and match results
+/// with the keys that start the next round.
+pub fn update_hud(
+    state: Res<State>,
+    game: Res<GameState>,
+    history: Res<History>,
+    match_play: Res<Match>,
+    viewer: Option<Res<Viewer>>,
+    mut texts: Query<(&Hud, &mut Text)>,
+    mut swatch: Query<&mut UiColor, (With<ForcedSwatch>, Without<Hud>)>,
+) {
+    let viewer_changed = viewer.as_ref().is_some_and(|viewer| viewer.is_changed());
+    if !state.is_changed() && !game.is_changed() && !match_play.is_changed() && !viewer_changed {
+        return;
+    }
+
+    let side = |player: Player| match player {
+        Player::White => "White",
+        Player::Black => "Black",
+    };
+
+    let turn = match game.winner() {
+        Some(winner) => format!("{} wins", side(winner)),
+        None => format!("{} to move", side(game.to_move())),
+    };
+    let turn = match *state {
+        State::Replay => format!("Replay: {}", turn),
+        _ => turn,
+    };
+
+    let forced = match (game.result(), game.forced()) {
+        (Some((_, reason)), _) => reason.description().to_string(),
+        (None, Some(color)) => format!("Forced: {:?}", color),
+        (None, None) => "Opening: any tower".to_string(),
+    };
+    swatch.single_mut().0 = match (game.winner(), game.forced()) {
+        (None, Some(color)) => color.color(),
+        _ => Color::NONE,
+    };
+
+    // Kept to short lines, the board starts a few hundred pixels in.
+    let mut info = format!(
+        "Move {}\nRound {}\nWhite {} - Black {}\nFirst to {}",
+        history.ply() + 1,
+        history.round() + 1,
+        match_play.score(Player::White),
+        match_play.score(Player::Black),
+        match_play.length().points()
+    );
+    match match_play.winner() {
+        Some(winner) => info += &format!("\n{} wins the match", side(winner)),
+        None if matches!(*state, State::Won(_)) => {
+            info += "\n\nLeft/Right: next round,\nfill from left/right"
+        }
+        None => {}
+    }
+    if let Some(viewer) = viewer.filter(|viewer| viewer.typed > 0) {
+        info += &format!("\n\nGo to ply {}: Enter", viewer.typed);
+    }
+
+    for (hud, mut text) in texts.iter_mut() {
+        text.sections[0].value = match hud {
+            Hud::Turn => turn.clone(),
+            Hud::Forced => forced.clone(),
+            Hud::Info => info.clone(),
+        };
+    }
+}
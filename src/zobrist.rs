@@ -0,0 +1,146 @@
+use crate::{
+    colors::Colors,
+    game::{Move, Player, Rank},
+};
+
+/// Random keys XORed together into a position hash: one per tower colour and
+/// square for each player, one per tower rank, one for Black to move and one
+/// per forced colour. Ranks only change between rounds, but hashing them keeps
+/// a table shared across rounds from mixing up positions.
+pub struct Keys {
+    towers: [[[u64; 64]; 8]; 2],
+    ranks: [[[u64; 5]; 8]; 2],
+    black_to_move: u64,
+    forced: [u64; 8],
+}
+
+pub const KEYS: Keys = Keys::new();
+
+impl Keys {
+    const fn new() -> Self {
+        let mut state = 0x9e37_79b9_7f4a_7c15;
+        let mut keys = Self {
+            towers: [[[0; 64]; 8]; 2],
+            ranks: [[[0; 5]; 8]; 2],
+            black_to_move: 0,
+            forced: [0; 8],
+        };
+
+        let mut player = 0;
+        while player < 2 {
+            let mut color = 0;
+            while color < 8 {
+                let mut square = 0;
+                while square < 64 {
+                    state = splitmix(state);
+                    keys.towers[player][color][square] = state;
+                    square += 1;
+                }
+                let mut rank = 0;
+                while rank < 5 {
+                    state = splitmix(state);
+                    keys.ranks[player][color][rank] = state;
+                    rank += 1;
+                }
+                color += 1;
+            }
+            player += 1;
+        }
+
+        state = splitmix(state);
+        keys.black_to_move = state;
+
+        let mut color = 0;
+        while color < 8 {
+            state = splitmix(state);
+            keys.forced[color] = state;
+            color += 1;
+        }
+
+        keys
+    }
+
+    pub fn tower(&self, player: Player, color: usize, square: u8) -> u64 {
+        self.towers[player as usize][color][square as usize]
+    }
+
+    pub fn rank(&self, player: Player, color: usize, rank: Rank) -> u64 {
+        self.ranks[player as usize][color][rank as usize]
+    }
+
+    pub fn to_move(&self, player: Player) -> u64 {
+        match player {
+            Player::White => 0,
+            Player::Black => self.black_to_move,
+        }
+    }
+
+    pub fn forced(&self, forced: Option<Colors>) -> u64 {
+        forced.map_or(0, |color| self.forced[color.index()])
+    }
+}
+
+const fn splitmix(state: u64) -> u64 {
+    let mut z = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Bound {
+    Exact,
+    /// The score is at least this, the search failed high.
+    Lower,
+    /// The score is at most this, the search failed low.
+    Upper,
+}
+
+#[derive(Clone, Copy)]
+pub struct Entry {
+    pub key: u64,
+    pub depth: u32,
+    pub score: i32,
+    pub bound: Bound,
+    pub best: Option<Move>,
+}
+
+/// A fixed-size hash table of search results, indexed by the low bits of the
+/// position hash. A new result takes over its slot, unless the slot holds a
+/// deeper result for the same position.
+pub struct TranspositionTable {
+    entries: Vec<Option<Entry>>,
+}
+
+impl TranspositionTable {
+    /// A table of at most `megabytes`, rounded down to a power of two entries.
+    pub fn new(megabytes: usize) -> Self {
+        let wanted = (megabytes << 20) / std::mem::size_of::<Option<Entry>>();
+        let len = if wanted.is_power_of_two() {
+            wanted.max(1)
+        } else {
+            (wanted.next_power_of_two() / 2).max(1)
+        };
+
+        Self {
+            entries: vec![None; len],
+        }
+    }
+
+    fn index(&self, key: u64) -> usize {
+        key as usize & (self.entries.len() - 1)
+    }
+
+    pub fn probe(&self, key: u64) -> Option<Entry> {
+        self.entries[self.index(key)].filter(|entry| entry.key == key)
+    }
+
+    pub fn store(&mut self, entry: Entry) {
+        let index = self.index(entry.key);
+        let slot = &mut self.entries[index];
+
+        if !matches!(slot, Some(old) if old.key == entry.key && old.depth > entry.depth) {
+            *slot = Some(entry);
+        }
+    }
+}